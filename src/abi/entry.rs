@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+
+use crate::types::hash::{keccak256, selector};
+
+/// Entry of the JSON ABI of a contract.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum AbiEntry {
+    Function(AbiFunction),
+    Event(AbiEvent),
+    Error(AbiError),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AbiFunction {
+    pub name: String,
+    pub inputs: Vec<AbiParam>,
+    pub outputs: Vec<AbiParam>,
+    pub state_mutability: StateMutability,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AbiEvent {
+    pub name: String,
+    pub inputs: Vec<AbiParam>,
+    pub anonymous: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AbiError {
+    pub name: String,
+    pub inputs: Vec<AbiParam>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AbiParam {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub components: Vec<AbiParam>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub indexed: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum StateMutability {
+    Pure,
+    View,
+    #[default]
+    Nonpayable,
+    Payable,
+}
+
+impl AbiParam {
+    /// Type as written in a canonical signature, with the tuples expanded.
+    pub fn canonical_type(&self) -> String {
+        match self.ty.strip_prefix("tuple") {
+            Some(suffix) => format!("({}){}", canonical_types(&self.components), suffix),
+            None => self.ty.clone(),
+        }
+    }
+}
+
+impl AbiFunction {
+    pub fn signature(&self) -> String {
+        format!("{}({})", self.name, canonical_types(&self.inputs))
+    }
+
+    pub fn selector(&self) -> [u8; 4] {
+        selector(&self.signature())
+    }
+}
+
+impl AbiEvent {
+    pub fn signature(&self) -> String {
+        format!("{}({})", self.name, canonical_types(&self.inputs))
+    }
+
+    pub fn topic(&self) -> [u8; 32] {
+        keccak256(self.signature().as_bytes())
+    }
+}
+
+impl AbiError {
+    pub fn signature(&self) -> String {
+        format!("{}({})", self.name, canonical_types(&self.inputs))
+    }
+
+    pub fn selector(&self) -> [u8; 4] {
+        selector(&self.signature())
+    }
+}
+
+impl AbiEntry {
+    pub fn signature(&self) -> String {
+        match self {
+            Self::Function(function) => function.signature(),
+            Self::Event(event) => event.signature(),
+            Self::Error(error) => error.signature(),
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Function(_) => "function",
+            Self::Event(_) => "event",
+            Self::Error(_) => "error",
+        }
+    }
+}
+
+fn canonical_types(params: &[AbiParam]) -> String {
+    params
+        .iter()
+        .map(AbiParam::canonical_type)
+        .collect::<Vec<_>>()
+        .join(",")
+}
@@ -0,0 +1,252 @@
+use super::entry::{AbiError, AbiEvent, AbiFunction, AbiParam, StateMutability};
+
+/// Parses a Solidity-style function signature - ex: `balanceOf(address owner) view returns (uint256)`.
+pub fn parse_function(signature: &str) -> Result<AbiFunction, String> {
+    let mut parser = SignatureParser::new(signature);
+    let (name, inputs) = parser.name_and_params(false)?;
+
+    let mut state_mutability = StateMutability::default();
+    let mut outputs = Vec::new();
+    while let Some(word) = parser.word() {
+        match word {
+            "pure" => state_mutability = StateMutability::Pure,
+            "view" => state_mutability = StateMutability::View,
+            "payable" => state_mutability = StateMutability::Payable,
+            "nonpayable" | "external" | "public" => (),
+            "returns" => outputs = parser.params(false)?,
+            _ => return Err(format!("unexpected `{}`", word)),
+        }
+    }
+    parser.end()?;
+
+    Ok(AbiFunction {
+        name,
+        inputs,
+        outputs,
+        state_mutability,
+    })
+}
+
+/// Parses a Solidity-style event signature - ex: `Transfer(address indexed from, address indexed to, uint256)`.
+pub fn parse_event(signature: &str) -> Result<AbiEvent, String> {
+    let mut parser = SignatureParser::new(signature);
+    let (name, inputs) = parser.name_and_params(true)?;
+
+    let mut anonymous = false;
+    while let Some(word) = parser.word() {
+        match word {
+            "anonymous" => anonymous = true,
+            _ => return Err(format!("unexpected `{}`", word)),
+        }
+    }
+    parser.end()?;
+
+    Ok(AbiEvent {
+        name,
+        inputs,
+        anonymous,
+    })
+}
+
+/// Parses a Solidity-style error signature - ex: `InsufficientBalance(uint256 available, uint256 required)`.
+pub fn parse_error(signature: &str) -> Result<AbiError, String> {
+    let mut parser = SignatureParser::new(signature);
+    let (name, inputs) = parser.name_and_params(false)?;
+    parser.end()?;
+
+    Ok(AbiError { name, inputs })
+}
+
+struct SignatureParser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> SignatureParser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self { input, pos: 0 }
+    }
+
+    fn name_and_params(&mut self, event: bool) -> Result<(String, Vec<AbiParam>), String> {
+        let Some(name) = self.word() else {
+            return Err("expected a name".to_owned());
+        };
+        let name = name.to_owned();
+        let params = self.params(event)?;
+        Ok((name, params))
+    }
+
+    fn params(&mut self, event: bool) -> Result<Vec<AbiParam>, String> {
+        self.expect('(')?;
+        let mut params = Vec::new();
+        if self.eat(')') {
+            return Ok(params);
+        }
+        loop {
+            params.push(self.param(event)?);
+            if self.eat(')') {
+                return Ok(params);
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn param(&mut self, event: bool) -> Result<AbiParam, String> {
+        let mut param = self.param_type()?;
+
+        let mut indexed = false;
+        while let Some(word) = self.peek_word() {
+            match word {
+                "indexed" if event => indexed = true,
+                "memory" | "calldata" | "storage" => (),
+                _ => break,
+            }
+            self.word();
+        }
+        if event {
+            param.indexed = Some(indexed);
+        }
+
+        if let Some(name) = self.word() {
+            param.name = name.to_owned();
+        }
+        Ok(param)
+    }
+
+    fn param_type(&mut self) -> Result<AbiParam, String> {
+        self.skip_whitespace();
+        let (ty, components) = if self.rest().starts_with('(') || self.rest().starts_with("tuple(") {
+            self.word();
+            let components = self.params(false)?;
+            ("tuple".to_owned(), components)
+        } else {
+            let Some(ty) = self.word() else {
+                return Err("expected a type".to_owned());
+            };
+            (canonical_elementary_type(ty)?, Vec::new())
+        };
+
+        let mut ty = ty;
+        while self.eat('[') {
+            let size_start = self.pos;
+            while self.rest().starts_with(|c: char| c.is_ascii_digit()) {
+                self.pos += 1;
+            }
+            let size = &self.input[size_start..self.pos];
+            self.expect(']')?;
+            ty = format!("{}[{}]", ty, size);
+        }
+
+        Ok(AbiParam {
+            name: String::new(),
+            ty,
+            components,
+            indexed: None,
+        })
+    }
+
+    fn word(&mut self) -> Option<&'a str> {
+        let word = self.peek_word()?;
+        self.pos += word.len();
+        Some(word)
+    }
+
+    fn peek_word(&mut self) -> Option<&'a str> {
+        self.skip_whitespace();
+        let rest = &self.input[self.pos..];
+        let len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '$'))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            None
+        } else {
+            Some(&rest[..len])
+        }
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(format!("expected `{}` at position {}", c, self.pos))
+        }
+    }
+
+    fn end(&mut self) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.rest().is_empty() {
+            Ok(())
+        } else {
+            Err(format!("unexpected `{}`", self.rest()))
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.rest().chars().next().filter(|c| c.is_whitespace()) {
+            self.pos += c.len_utf8();
+        }
+    }
+}
+
+fn canonical_elementary_type(ty: &str) -> Result<String, String> {
+    let canonical = match ty {
+        "uint" => "uint256".to_owned(),
+        "int" => "int256".to_owned(),
+        "address" | "bool" | "string" | "bytes" | "function" => ty.to_owned(),
+        _ => {
+            let sized =
+                [("uint", 8, 256), ("int", 8, 256), ("bytes", 1, 32)]
+                    .iter()
+                    .any(
+                        |(prefix, step, max)| match ty.strip_prefix(prefix).map(str::parse::<usize>) {
+                            Some(Ok(size)) => size > 0 && size <= *max && size.is_multiple_of(*step),
+                            _ => false,
+                        },
+                    );
+            if !sized {
+                return Err(format!("unknown type `{}`", ty));
+            }
+            ty.to_owned()
+        },
+    };
+    Ok(canonical)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_parse_signatures() {
+        let function = parse_function("transfer(address to, uint amount) returns (bool)").unwrap();
+        assert_eq!(function.signature(), "transfer(address,uint256)");
+        assert_eq!(hex::encode(function.selector()), "a9059cbb");
+
+        let function = parse_function("submit((uint256 a, address[] b)[2] orders) payable").unwrap();
+        assert_eq!(function.signature(), "submit((uint256,address[])[2])");
+        assert_eq!(function.inputs[0].ty, "tuple[2]");
+        assert_eq!(function.state_mutability, StateMutability::Payable);
+
+        let event = parse_event("Transfer(address indexed from, address indexed to, uint256 value)").unwrap();
+        assert_eq!(
+            hex::encode(event.topic()),
+            "ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+        );
+
+        assert!(parse_error("Unauthorized(uint7)").is_err());
+    }
+}
@@ -4,7 +4,7 @@ use pest::iterators::Pair;
 use quick_impl::QuickImpl;
 
 use super::variable::RVariable;
-use super::{RCompileVariable, RHexLiteral, RStringLiteral};
+use super::{RCompileVariable, RHexLiteral, RLiteral, RStringLiteral};
 use crate::parser::parser::{get_next, map_unique_child, FromPair, Located, Rule};
 
 #[derive(Debug, Clone, QuickImpl)]
@@ -61,14 +61,81 @@ impl FromPair for RAttributeEquality {
     }
 }
 
+/// Condition `NAME == value` on a compile variable.
+#[derive(Debug, Clone)]
+pub struct RAttributeComparison {
+    pub name: Located<RVariable>,
+    pub value: Located<RLiteral>,
+}
+
+impl RAttributeComparison {
+    pub fn name_str(&self) -> &str {
+        self.name.as_str()
+    }
+}
+
+impl FromPair for RAttributeComparison {
+    fn from_pair(attribute_comparison: Pair<Rule>) -> Result<Self, pest::error::Error<Rule>> {
+        assert!(attribute_comparison.as_rule() == Rule::attribute_comparison);
+
+        let mut inner = attribute_comparison.into_inner();
+
+        let name = Located::<RVariable>::from_pair(get_next(&mut inner, Rule::variable))?;
+
+        _ = get_next(&mut inner, Rule::double_eq);
+
+        let value = Located::<RLiteral>::from_pair(get_next(&mut inner, Rule::literal))?;
+
+        assert!(inner.next().is_none());
+
+        Ok(Self { name, value })
+    }
+}
+
+/// Items taken from the stack and left on it - ex: `a, b -> c`.
+#[derive(Debug, Clone)]
+pub struct RAttributeStack {
+    pub inputs: Vec<Located<RVariable>>,
+    pub outputs: Vec<Located<RVariable>>,
+}
+
+impl FromPair for RAttributeStack {
+    fn from_pair(attribute_stack: Pair<Rule>) -> Result<Self, pest::error::Error<Rule>> {
+        assert!(attribute_stack.as_rule() == Rule::attribute_stack);
+
+        let mut inputs = Vec::<Located<RVariable>>::new();
+        let mut outputs = Vec::<Located<RVariable>>::new();
+        let mut after_arrow = false;
+        for child in attribute_stack.into_inner() {
+            match child.as_rule() {
+                Rule::arrow => after_arrow = true,
+                Rule::stack_names => {
+                    let names = if after_arrow { &mut outputs } else { &mut inputs };
+                    for name in child.into_inner().filter(|name| name.as_rule() == Rule::variable) {
+                        names.push(Located::<RVariable>::from_pair(name)?);
+                    }
+                },
+                _ => unreachable!(),
+            }
+        }
+
+        Ok(Self { inputs, outputs })
+    }
+}
+
 #[derive(Debug, Clone, QuickImpl)]
 pub enum RAttributeArg {
+    Not(Box<Located<RAttributeArg>>),
+    #[quick_impl(impl From)]
+    Comparison(RAttributeComparison),
+    #[quick_impl(impl From)]
+    Stack(RAttributeStack),
     #[quick_impl(impl From)]
     AttributeEquality(RAttributeEquality),
     #[quick_impl(impl From)]
     Variable(RVariable),
     #[quick_impl(impl From)]
-    StringLiteral(#[allow(unused)] RStringLiteral),
+    StringLiteral(RStringLiteral),
 }
 
 impl FromPair for RAttributeArg {
@@ -76,6 +143,17 @@ impl FromPair for RAttributeArg {
         assert!(attribute_arg.as_rule() == Rule::attribute_arg);
 
         map_unique_child(attribute_arg, |inner| match inner.as_rule() {
+            Rule::attribute_not => {
+                let mut not_inner = inner.into_inner();
+                _ = get_next(&mut not_inner, Rule::not_keyword);
+                _ = get_next(&mut not_inner, Rule::open_paren);
+                let arg = Located::<RAttributeArg>::from_pair(get_next(&mut not_inner, Rule::attribute_arg))?;
+                _ = get_next(&mut not_inner, Rule::close_paren);
+                assert!(not_inner.next().is_none());
+                Ok(Self::Not(Box::new(arg)))
+            },
+            Rule::attribute_comparison => Ok(RAttributeComparison::from_pair(inner)?.into()),
+            Rule::attribute_stack => Ok(RAttributeStack::from_pair(inner)?.into()),
             Rule::attribute_equality => Ok(RAttributeEquality::from_pair(inner)?.into()),
             Rule::variable => Ok(RVariable::from_pair(inner)?.into()),
             Rule::string_literal => Ok(RStringLiteral::from_pair(inner)?.into()),
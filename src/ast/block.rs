@@ -5,6 +5,7 @@ use super::attribute::WithAttributes;
 use super::function::RFunction;
 use super::variable::{RVariable, RVariableWithField};
 use super::RHexAlias;
+use crate::parser::error::new_error_from_pair;
 use crate::parser::parser::{get_next, map_unique_child, FromPair, Located, Rule};
 
 #[derive(Debug, Clone, QuickImpl)]
@@ -70,6 +71,56 @@ impl FromPair for RBlockRef {
     }
 }
 
+/// Items written `count` times, each copy seeing the index of its repetition as the constant `index`.
+#[derive(Debug, Clone)]
+pub struct RRepeat {
+    pub count: Located<RHexAlias>,
+    pub index: Option<Located<RVariable>>,
+    pub items: Vec<Located<WithAttributes<Located<RBlockItem>>>>,
+}
+
+impl FromPair for RRepeat {
+    fn from_pair(repeat: Pair<Rule>) -> Result<Self, pest::error::Error<Rule>> {
+        assert!(repeat.as_rule() == Rule::repeat);
+
+        let mut repeat_inner = repeat.into_inner();
+
+        _ = get_next(&mut repeat_inner, Rule::repeat_keyword);
+        _ = get_next(&mut repeat_inner, Rule::open_paren);
+
+        let count = Located::<RHexAlias>::from_pair(get_next(&mut repeat_inner, Rule::hex_alias))?;
+
+        let index = match repeat_inner.next().unwrap().as_rule() {
+            Rule::comma => {
+                let index = Located::<RVariable>::from_pair(get_next(&mut repeat_inner, Rule::variable))?;
+                _ = get_next(&mut repeat_inner, Rule::close_paren);
+                Some(index)
+            },
+            Rule::close_paren => None,
+            _ => unreachable!(),
+        };
+
+        _ = get_next(&mut repeat_inner, Rule::open_brace);
+
+        let mut items = Vec::<Located<WithAttributes<Located<RBlockItem>>>>::new();
+        while let Some(block_item_with_attr) = repeat_inner.next() {
+            match block_item_with_attr.as_rule() {
+                Rule::block_item_with_attr => {
+                    items.push(Located::<WithAttributes<Located<RBlockItem>>>::from_pair(
+                        block_item_with_attr,
+                    )?);
+                },
+                Rule::close_brace => {
+                    assert!(repeat_inner.next().is_none());
+                    return Ok(Self { count, index, items });
+                },
+                _ => unreachable!(),
+            }
+        }
+        unreachable!();
+    }
+}
+
 #[derive(Debug, Clone, QuickImpl)]
 pub enum RBlockItem {
     #[quick_impl(impl From)]
@@ -78,10 +129,16 @@ pub enum RBlockItem {
     HexAlias(RHexAlias),
     #[quick_impl(impl From)]
     BlockRef(RBlockRef),
+    #[quick_impl(impl From)]
+    Repeat(RRepeat),
 }
 
 impl FromPair for RBlockItem {
     fn from_pair(block_item: Pair<Rule>) -> Result<Self, pest::error::Error<Rule>> {
+        // a repetition is written in place of a block item
+        if block_item.as_rule() == Rule::repeat {
+            return Ok(RRepeat::from_pair(block_item)?.into());
+        }
         assert!(block_item.as_rule() == Rule::block_item);
 
         map_unique_child(block_item, |child| match child.as_rule() {
@@ -93,10 +150,51 @@ impl FromPair for RBlockItem {
     }
 }
 
+/// Maximum number of items on the stack of the EVM.
+const MAX_STACK_SIZE: usize = 1024;
+
+/// Number of items a block takes from the stack and leaves on it - ex: `(in: 2, out: 1)`.
+#[derive(Debug, Clone)]
+pub struct RStackSignature {
+    pub inputs: usize,
+    pub outputs: usize,
+}
+
+impl FromPair for RStackSignature {
+    fn from_pair(stack_signature: Pair<Rule>) -> Result<Self, pest::error::Error<Rule>> {
+        assert!(stack_signature.as_rule() == Rule::stack_signature);
+
+        let mut counts = Vec::<usize>::new();
+        for digits in stack_signature
+            .into_inner()
+            .filter(|child| child.as_rule() == Rule::decimal_digits)
+        {
+            match digits.as_str().replace('_', "").parse::<usize>() {
+                Ok(count) if count <= MAX_STACK_SIZE => counts.push(count),
+                Ok(_) => {
+                    return Err(new_error_from_pair(
+                        &digits,
+                        format!("The stack holds at most {} items.", MAX_STACK_SIZE),
+                    ))
+                },
+                Err(err) => return Err(new_error_from_pair(&digits, err.to_string())),
+            }
+        }
+        assert!(counts.len() == 2);
+
+        Ok(Self {
+            inputs: counts[0],
+            outputs: counts[1],
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct RBlock {
     pub name: Located<RVariable>,
     pub abstr: bool,
+    pub data: bool,
+    pub signature: Option<Located<RStackSignature>>,
     pub items: Vec<Located<WithAttributes<Located<RBlockItem>>>>,
 }
 
@@ -112,18 +210,31 @@ impl FromPair for RBlock {
 
         let mut block_decl_inner = block_decl.into_inner();
 
-        let abstr = match block_decl_inner.next().unwrap().as_rule() {
-            Rule::block_keyword => false,
+        let (abstr, data) = match block_decl_inner.next().unwrap().as_rule() {
+            Rule::block_keyword => (false, false),
             Rule::abstract_keyword => {
                 _ = get_next(&mut block_decl_inner, Rule::block_keyword);
-                true
+                (true, false)
+            },
+            Rule::data_keyword => {
+                _ = get_next(&mut block_decl_inner, Rule::block_keyword);
+                (false, true)
             },
             _ => unreachable!(),
         };
 
         let name = Located::<RVariable>::from_pair(get_next(&mut block_decl_inner, Rule::variable))?;
 
-        _ = get_next(&mut block_decl_inner, Rule::open_brace);
+        let signature = match block_decl_inner.next().unwrap() {
+            stack_signature if stack_signature.as_rule() == Rule::stack_signature => {
+                _ = get_next(&mut block_decl_inner, Rule::open_brace);
+                Some(Located::<RStackSignature>::from_pair(stack_signature)?)
+            },
+            open_brace => {
+                assert!(open_brace.as_rule() == Rule::open_brace);
+                None
+            },
+        };
 
         let mut items = Vec::<Located<WithAttributes<Located<RBlockItem>>>>::new();
         while let Some(block_item_with_attr) = block_decl_inner.next() {
@@ -135,7 +246,13 @@ impl FromPair for RBlock {
                 },
                 Rule::close_brace => {
                     assert!(block_decl_inner.next() == None);
-                    return Ok(RBlock { name, abstr, items });
+                    return Ok(RBlock {
+                        name,
+                        abstr,
+                        data,
+                        signature,
+                        items,
+                    });
                 },
                 _ => unreachable!(),
             }
@@ -2,14 +2,16 @@ use pest::iterators::Pair;
 use quick_impl::QuickImpl;
 
 use super::variable::RVariable;
-use super::RCompileVariable;
-use crate::ast::literal::RHexLiteral;
+use super::{RCompileVariable, RNegation};
+use crate::ast::literal::RLiteral;
 use crate::parser::parser::{get_next, map_unique_child, FromPair, Located, Rule};
 
 #[derive(Debug, Clone, QuickImpl)]
 pub enum RConstantArg {
     #[quick_impl(impl From)]
-    HexLiteral(RHexLiteral),
+    Negation(RNegation),
+    #[quick_impl(impl From)]
+    Literal(RLiteral),
     #[quick_impl(impl From)]
     CompileVariable(RCompileVariable),
 }
@@ -19,7 +21,8 @@ impl FromPair for RConstantArg {
         assert!(constant_arg.as_rule() == Rule::const_arg);
 
         map_unique_child(constant_arg, |child| match child.as_rule() {
-            Rule::hex_literal => Ok(RHexLiteral::from_pair(child)?.into()),
+            Rule::negation => Ok(RNegation::from_pair(child)?.into()),
+            Rule::literal => Ok(RLiteral::from_pair(child)?.into()),
             Rule::compile_variable => Ok(RCompileVariable::from_pair(child)?.into()),
             _ => unreachable!(),
         })
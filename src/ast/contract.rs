@@ -10,7 +10,7 @@ use crate::parser::parser::{get_next, FromPair, Located, Rule};
 pub struct RContract {
     pub name: Located<RVariable>,
     pub blocks: Vec<Located<WithAttributes<Located<RBlock>>>>,
-    pub constants: Vec<Located<RConstant>>,
+    pub constants: Vec<Located<WithAttributes<Located<RConstant>>>>,
 }
 
 impl RContract {
@@ -35,14 +35,14 @@ impl FromPair for RContract {
         _ = get_next(&mut contract_decl_inner, Rule::open_brace);
 
         let mut blocks = Vec::<Located<WithAttributes<Located<RBlock>>>>::new();
-        let mut constants = Vec::<Located<RConstant>>::new();
+        let mut constants = Vec::<Located<WithAttributes<Located<RConstant>>>>::new();
         while let Some(contract_item) = contract_decl_inner.next() {
             match contract_item.as_rule() {
                 Rule::block_decl_with_attr => {
                     blocks.push(Located::<WithAttributes<Located<RBlock>>>::from_pair(contract_item)?);
                 },
-                Rule::const_decl => {
-                    constants.push(Located::<RConstant>::from_pair(contract_item)?);
+                Rule::const_decl_with_attr => {
+                    constants.push(Located::<WithAttributes<Located<RConstant>>>::from_pair(contract_item)?);
                 },
                 Rule::close_brace => {
                     assert!(contract_decl_inner.next() == None);
@@ -5,13 +5,94 @@ use super::variable::{RVariable, RVariableWithField};
 use super::{RConcatenation, RHexAlias};
 use crate::parser::parser::{get_next, map_unique_child, FromPair, Located, Rule};
 
+#[derive(Debug, Clone)]
+pub struct RCreate2 {
+    pub deployer: Located<RHexAlias>,
+    pub salt: Located<RHexAlias>,
+    pub contract: Located<RVariable>,
+}
+
+impl FromPair for RCreate2 {
+    fn from_pair(create2_call: Pair<Rule>) -> Result<Self, pest::error::Error<Rule>> {
+        assert!(create2_call.as_rule() == Rule::create2_call);
+
+        let mut create2_inner = create2_call.into_inner();
+
+        _ = get_next(&mut create2_inner, Rule::create2_keyword);
+        _ = get_next(&mut create2_inner, Rule::open_paren);
+
+        let deployer = Located::<RHexAlias>::from_pair(get_next(&mut create2_inner, Rule::hex_alias))?;
+
+        _ = get_next(&mut create2_inner, Rule::comma);
+
+        let salt = Located::<RHexAlias>::from_pair(get_next(&mut create2_inner, Rule::hex_alias))?;
+
+        _ = get_next(&mut create2_inner, Rule::comma);
+
+        let contract = Located::<RVariable>::from_pair(get_next(&mut create2_inner, Rule::variable))?;
+
+        _ = get_next(&mut create2_inner, Rule::close_paren);
+        assert!(create2_inner.next().is_none());
+
+        Ok(Self {
+            deployer,
+            salt,
+            contract,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RArithmeticOperator {
+    Add,
+    Sub,
+    Mul,
+}
+
+/// Operands separated by operators, as written.
+#[derive(Debug, Clone)]
+pub struct RArithmetic {
+    pub first: Located<RHexAlias>,
+    pub rest: Vec<(RArithmeticOperator, Located<RHexAlias>)>,
+}
+
+impl FromPair for RArithmetic {
+    fn from_pair(arithmetic: Pair<Rule>) -> Result<Self, pest::error::Error<Rule>> {
+        assert!(arithmetic.as_rule() == Rule::arithmetic);
+
+        let mut arithmetic_inner = arithmetic.into_inner();
+
+        let first = Located::<RHexAlias>::from_pair(get_next(&mut arithmetic_inner, Rule::hex_alias))?;
+
+        let mut rest = Vec::<(RArithmeticOperator, Located<RHexAlias>)>::new();
+        while let Some(operator) = arithmetic_inner.next() {
+            assert!(operator.as_rule() == Rule::arithmetic_operator);
+
+            let operator = match operator.into_inner().next().unwrap().as_rule() {
+                Rule::plus => RArithmeticOperator::Add,
+                Rule::minus => RArithmeticOperator::Sub,
+                Rule::star => RArithmeticOperator::Mul,
+                _ => unreachable!(),
+            };
+            let operand = Located::<RHexAlias>::from_pair(get_next(&mut arithmetic_inner, Rule::hex_alias))?;
+            rest.push((operator, operand));
+        }
+
+        Ok(Self { first, rest })
+    }
+}
+
 #[derive(Debug, Clone, QuickImpl)]
 pub enum RFunctionArg {
+    #[quick_impl(impl From)]
+    Create2(RCreate2),
     #[quick_impl(impl From)]
     VariableWithField(RVariableWithField),
     #[quick_impl(impl From)]
     VariablesConcat(RConcatenation),
     #[quick_impl(impl From)]
+    Arithmetic(RArithmetic),
+    #[quick_impl(impl From)]
     HexAlias(RHexAlias),
 }
 
@@ -20,8 +101,10 @@ impl FromPair for RFunctionArg {
         assert!(function_arg.as_rule() == Rule::function_arg);
 
         map_unique_child(function_arg, |child| match child.as_rule() {
+            Rule::create2_call => Ok(RCreate2::from_pair(child)?.into()),
             Rule::variable_with_field => Ok(RVariableWithField::from_pair(child)?.into()),
             Rule::concatenation => Ok(RConcatenation::from_pair(child)?.into()),
+            Rule::arithmetic => Ok(RArithmetic::from_pair(child)?.into()),
             Rule::hex_alias => Ok(RHexAlias::from_pair(child)?.into()),
             _ => unreachable!(),
         })
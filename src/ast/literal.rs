@@ -1,9 +1,36 @@
 use bytes::Bytes;
 use pest::iterators::Pair;
+use quick_impl::QuickImpl;
 
 use crate::parser::error::new_error_from_pair;
 use crate::parser::parser::{map_unique_child, FromPair, Rule};
 
+#[derive(Debug, Clone, QuickImpl)]
+pub enum RLiteral {
+    #[quick_impl(impl From)]
+    Hex(RHexLiteral),
+    #[quick_impl(impl From)]
+    Decimal(RDecimalLiteral),
+    #[quick_impl(impl From)]
+    String(RStringLiteral),
+    #[quick_impl(impl From)]
+    Address(RAddressLiteral),
+}
+
+impl FromPair for RLiteral {
+    fn from_pair(literal: Pair<Rule>) -> Result<Self, pest::error::Error<Rule>> {
+        assert!(literal.as_rule() == Rule::literal);
+
+        map_unique_child(literal, |child| match child.as_rule() {
+            Rule::hex_literal => Ok(RHexLiteral::from_pair(child)?.into()),
+            Rule::decimal_literal => Ok(RDecimalLiteral::from_pair(child)?.into()),
+            Rule::string_literal => Ok(RStringLiteral::from_pair(child)?.into()),
+            Rule::address_literal => Ok(RAddressLiteral::from_pair(child)?.into()),
+            _ => unreachable!(),
+        })
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct RHexLiteral(pub Bytes);
 
@@ -50,3 +77,40 @@ impl FromPair for RStringLiteral {
         }))
     }
 }
+
+/// Decimal number, as its digits in wei - ex: `1_000 gwei` is `1000000000000`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RDecimalLiteral(pub String);
+
+impl FromPair for RDecimalLiteral {
+    fn from_pair(decimal_literal: Pair<Rule>) -> Result<Self, pest::error::Error<Rule>> {
+        assert!(decimal_literal.as_rule() == Rule::decimal_literal);
+
+        let mut digits = String::new();
+        for child in decimal_literal.into_inner() {
+            match child.as_rule() {
+                Rule::decimal_digits => digits.extend(child.as_str().chars().filter(|c| *c != '_')),
+                Rule::decimal_unit => digits.push_str(match child.as_str() {
+                    "wei" => "",
+                    "gwei" => "000000000",
+                    "ether" => "000000000000000000",
+                    _ => unreachable!(),
+                }),
+                _ => unreachable!(),
+            }
+        }
+        Ok(Self(digits))
+    }
+}
+
+/// Address, as its 40 hexadecimal digits written in the source.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RAddressLiteral(pub String);
+
+impl FromPair for RAddressLiteral {
+    fn from_pair(address_literal: Pair<Rule>) -> Result<Self, pest::error::Error<Rule>> {
+        assert!(address_literal.as_rule() == Rule::address_literal);
+
+        Ok(Self(address_literal.as_str().strip_prefix("0x").unwrap().to_owned()))
+    }
+}
@@ -1,7 +1,7 @@
 use pest::iterators::Pair;
 use quick_impl::QuickImpl;
 
-use super::RHexLiteral;
+use super::RLiteral;
 use crate::parser::parser::{get_next, map_unique_child, FromPair, Located, Rule};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -73,7 +73,9 @@ impl RCompileVariable {
 #[derive(Debug, Clone, QuickImpl)]
 pub enum RHexAlias {
     #[quick_impl(impl From)]
-    HexLiteral(RHexLiteral),
+    Negation(RNegation),
+    #[quick_impl(impl From)]
+    Literal(RLiteral),
     #[quick_impl(impl From)]
     Variable(RVariable),
     #[quick_impl(impl From)]
@@ -85,7 +87,8 @@ impl FromPair for RHexAlias {
         assert!(hex_alias.as_rule() == Rule::hex_alias);
 
         map_unique_child(hex_alias, |child| match child.as_rule() {
-            Rule::hex_literal => Ok(RHexLiteral::from_pair(child)?.into()),
+            Rule::negation => Ok(RNegation::from_pair(child)?.into()),
+            Rule::literal => Ok(RLiteral::from_pair(child)?.into()),
             Rule::variable => Ok(RVariable::from_pair(child)?.into()),
             Rule::compile_variable => Ok(RCompileVariable::from_pair(child)?.into()),
             _ => unreachable!(),
@@ -93,6 +96,24 @@ impl FromPair for RHexAlias {
     }
 }
 
+/// Two's complement opposite of a value - ex: `-1`, `-0x20` or `-OFFSET`.
+#[derive(Debug, Clone)]
+pub struct RNegation(pub Box<Located<RHexAlias>>);
+
+impl FromPair for RNegation {
+    fn from_pair(negation: Pair<Rule>) -> Result<Self, pest::error::Error<Rule>> {
+        assert!(negation.as_rule() == Rule::negation);
+
+        let mut negation_inner = negation.into_inner();
+
+        _ = get_next(&mut negation_inner, Rule::minus);
+
+        let value = Located::<RHexAlias>::from_pair(get_next(&mut negation_inner, Rule::hex_alias))?;
+
+        Ok(Self(Box::new(value)))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct RConcatenation(pub Vec<Located<RHexAlias>>);
 
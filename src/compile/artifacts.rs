@@ -1,23 +1,123 @@
-use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 use bytes::Bytes;
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
-use super::settings::{deserialize_bytes, serialize_bytes};
+use super::foundry::{foundry_artifact, foundry_artifact_path};
+use super::settings::{deserialize_bytes, serialize_bytes, CompilerSettings};
+use crate::abi::entry::AbiEntry;
+use crate::parser::parser::Location;
+use crate::pre_processing::opcode::push_length;
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(deny_unknown_fields, rename_all = "camelCase")]
 pub struct Artifacts {
     pub main: String,
-    pub contracts: HashMap<String, ContractArtifacts>,
+    /// Artifacts of the main contract, then of the contracts it uses, each contract before the ones it uses.
+    pub contracts: IndexMap<String, ContractArtifacts>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build_info: Option<BuildInfo>,
+}
+
+/// What a compilation was made from, to reproduce it.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct BuildInfo {
+    pub compiler_version: String,
+    /// Settings of the compilation, with the resolved compile variables and seed of the random filling.
+    pub settings: CompilerSettings,
+    /// Keccak256 hash of the source.
+    #[serde(serialize_with = "serialize_bytes", deserialize_with = "deserialize_bytes")]
+    pub source_hash: Bytes,
 }
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(deny_unknown_fields, rename_all = "camelCase")]
 pub struct ContractArtifacts {
-    pub blocks: HashMap<String, BlockArtifacts>,
+    /// Blocks in the order of the bytecode. The blocks inlined into a block follow it.
+    pub blocks: IndexMap<String, BlockArtifacts>,
     #[serde(serialize_with = "serialize_bytes", deserialize_with = "deserialize_bytes")]
     pub bytecode: Bytes,
+    #[serde(default)]
+    pub abi: Vec<AbiEntry>,
+    /// Whether the contract is marked with `#[initcode]`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub initcode: bool,
+    /// Parts of the source the bytecode comes from, sorted by pc.
+    #[serde(skip)]
+    pub source_spans: Vec<SourceSpan>,
+    /// What the compiler did at some points of the bytecode, sorted by pc.
+    #[serde(skip)]
+    pub annotations: Vec<Annotation>,
+}
+
+/// Part of the source of the bytecode from the byte `pc`, until the next span. Without location, the bytes are
+/// generated by the compiler.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceSpan {
+    pub pc: usize,
+    pub location: Option<Location>,
+}
+
+/// Note on the bytecode at the byte `pc`, shown in the assembly listing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Annotation {
+    pub pc: usize,
+    pub kind: AnnotationKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnnotationKind {
+    /// Start of a block, or of a block copied with `*`.
+    Block(String),
+    /// Start of a copy of an abstract block.
+    Abstract(String),
+    /// `size` bytes emitted as is: raw bytes, or the bytecode of the contract `contract`.
+    Data { size: usize, contract: Option<String> },
+    /// Push of zero replaced by `push0`.
+    Push0,
+    /// Push of the value replaced by the opcode assumed to push it.
+    Assumed(Bytes),
+    /// Push of the value replaced by the push of its bitwise not, followed by `not`.
+    Not(Bytes),
+    /// Bytes added so that the next block starts on an opcode.
+    Filler(usize),
+    /// `invalid` opcode between the code and the data blocks.
+    DataGuard,
+    /// Push hiding the jump destinations of the data which follows.
+    JumpdestGuard,
+}
+
+/// Format of the artifacts written to files.
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ArtifactFormat {
+    /// The `Artifacts` of the compilation, as `<Contract>.json`.
+    #[default]
+    Meplang,
+    /// The artifact of the contract in the layout of Foundry, as `<File>.mep/<Contract>.json`.
+    Foundry,
+}
+
+impl ArtifactFormat {
+    /// Path of the artifacts of the contract `contract_name` of the file `source_path`, inside `out_dir`.
+    pub fn path(self, out_dir: &Path, source_path: &str, contract_name: &str) -> PathBuf {
+        match self {
+            Self::Meplang => out_dir.join(format!("{}.json", contract_name)),
+            Self::Foundry => foundry_artifact_path(out_dir, source_path, contract_name),
+        }
+    }
+
+    /// Content of the file of the artifacts of the main contract of a compilation.
+    pub fn content(self, artifacts: &Artifacts, push0: bool) -> String {
+        match self {
+            Self::Meplang => serde_json::to_string_pretty(artifacts).unwrap(),
+            Self::Foundry => {
+                serde_json::to_string_pretty(&foundry_artifact(artifacts.main_artifacts(), push0)).unwrap()
+            },
+        }
+    }
 }
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -33,7 +133,7 @@ impl Artifacts {
     }
 
     pub fn main_artifacts(&self) -> &ContractArtifacts {
-        &self.contracts.get(&self.main).unwrap()
+        self.contracts.get(&self.main).unwrap()
     }
 }
 
@@ -50,4 +150,86 @@ impl ContractArtifacts {
         assert!(end >= ba.pc);
         ba.size = end - ba.pc;
     }
+
+    /// Changes of the size of the bytecode and of the blocks since `previous`, one line per change.
+    pub fn size_changes(&self, previous: &ContractArtifacts) -> Vec<String> {
+        let mut res = Vec::<String>::new();
+        let diff = |previous: usize, current: usize| {
+            format!(
+                "{} -> {} bytes ({:+})",
+                previous,
+                current,
+                current as isize - previous as isize
+            )
+        };
+
+        if self.bytecode.len() != previous.bytecode.len() {
+            res.push(format!(
+                "bytecode: {}",
+                diff(previous.bytecode.len(), self.bytecode.len())
+            ));
+        }
+
+        let removed = previous.blocks.keys().filter(|name| !self.blocks.contains_key(*name));
+        for name in self.blocks.keys().chain(removed) {
+            match (previous.blocks.get(name), self.blocks.get(name)) {
+                (Some(before), Some(after)) if before.size != after.size => {
+                    res.push(format!("block `{}`: {}", name, diff(before.size, after.size)));
+                },
+                (Some(before), None) => res.push(format!("block `{}`: removed ({} bytes)", name, before.size)),
+                (None, Some(after)) => res.push(format!("block `{}`: added ({} bytes)", name, after.size)),
+                _ => (),
+            }
+        }
+        res
+    }
+
+    /// Source map of the bytecode in the format of solc: `start:length:file:jump` for each instruction, separated
+    /// by `;`, where the fields equal to those of the previous instruction are omitted. The bytes generated by the
+    /// compiler map to the file `-1`.
+    pub fn source_map(&self, file_index: usize) -> String {
+        let mut entries = Vec::<String>::new();
+        let mut previous = Vec::<String>::new();
+        let mut span_index = 0;
+        let mut pc = 0;
+        while pc < self.bytecode.len() {
+            while span_index + 1 < self.source_spans.len() && self.source_spans[span_index + 1].pc <= pc {
+                span_index += 1;
+            }
+            let location = self
+                .source_spans
+                .get(span_index)
+                .filter(|span| span.pc <= pc)
+                .and_then(|span| span.location.as_ref());
+            let fields = match location {
+                Some(location) => vec![
+                    location.start.to_string(),
+                    (location.end - location.start).to_string(),
+                    file_index.to_string(),
+                    "-".to_owned(),
+                ],
+                None => vec!["0".to_owned(), "0".to_owned(), "-1".to_owned(), "-".to_owned()],
+            };
+
+            let mut entry = fields
+                .iter()
+                .enumerate()
+                .map(|(index, field)| {
+                    if previous.get(index) == Some(field) {
+                        ""
+                    } else {
+                        field.as_str()
+                    }
+                })
+                .collect::<Vec<_>>();
+            while entry.last() == Some(&"") {
+                entry.pop();
+            }
+            entries.push(entry.join(":"));
+            previous = fields;
+
+            pc += 1 + push_length(self.bytecode[pc]).unwrap_or(0);
+        }
+        entries.join(";")
+    }
 }
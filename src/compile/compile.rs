@@ -1,25 +1,39 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use bytes::{BufMut, Bytes, BytesMut};
 
-use super::artifacts::ContractArtifacts;
-use super::fillers::{fill_with_pattern, fill_with_random};
+use super::artifacts::{Annotation, AnnotationKind, ContractArtifacts, SourceSpan};
+use super::fillers::{fill_with_pattern, fill_with_random, FillingRng};
+use super::jumpdests::{jumpdest_guard, pending_push_bytes, valid_jumpdests};
 use super::settings::{CompilerSettings, FillingPatern};
+use crate::abi::entry::AbiEntry;
 use crate::compile::artifacts::Artifacts;
-use crate::pre_processing::opcode::{push_length, PUSH0, PUSH1, PUSH2, PUSH32};
-use crate::pre_processing::pre_processing::{Block, BlockItemInner, Contract, PushInner};
+use crate::diagnostics::codes::Code;
+use crate::diagnostics::diagnostic::{Diagnostic, Diagnostics};
+use crate::pre_processing::opcode::{push_length, INVALID, NOT, PUSH0, PUSH1, PUSH2, PUSH32};
+use crate::pre_processing::pre_processing::{BlockItemInner, Contract, Push, PushInner};
 use crate::types::bytes32::Bytes32;
+use crate::types::hash::keccak256;
 
-pub fn compile_contracts(contracts: Vec<Contract>, settings: CompilerSettings) -> Artifacts {
+pub fn compile_contracts(
+    contracts: Vec<Contract>,
+    settings: CompilerSettings,
+    warnings: &mut Diagnostics,
+) -> Result<Artifacts, Diagnostic> {
     let mut artifacts = Artifacts::default();
     artifacts.main = contracts[0].name.clone();
 
     let mut bytecodes = HashMap::<usize, Bytes>::new();
 
     for contract_index in (0..contracts.len()).rev() {
-        let contract_artifacts = compile_contract(&contracts[contract_index].blocks, &bytecodes, &settings);
+        let mut contract_artifacts = compile_contract(&contracts, contract_index, &bytecodes, &settings, warnings)?;
         let contract_name = &contracts[contract_index].name;
 
+        check_size(&contracts[contract_index], &contract_artifacts, &settings)?;
+        check_abi(&contracts[contract_index], warnings);
+        contract_artifacts.abi = contracts[contract_index].abi.clone();
+        contract_artifacts.initcode = contracts[contract_index].initcode;
+
         artifacts.contracts.insert(contract_name.clone(), contract_artifacts);
 
         bytecodes.insert(
@@ -27,8 +41,215 @@ pub fn compile_contracts(contracts: Vec<Contract>, settings: CompilerSettings) -
             artifacts.contracts.get(contract_name).unwrap().bytecode.clone(),
         );
     }
+    artifacts.contracts.reverse();
+
+    Ok(artifacts)
+}
+
+/// Checks the size of the bytecode against the limit of the runtime bytecode, or of the initcode if the contract
+/// is marked with `#[initcode]`. The size of each block is reported when the limit is exceeded.
+fn check_size(
+    contract: &Contract,
+    artifacts: &ContractArtifacts,
+    settings: &CompilerSettings,
+) -> Result<(), Diagnostic> {
+    let (limit, kind) = if contract.initcode {
+        (settings.max_initcode_size, "initcode")
+    } else {
+        (settings.max_runtime_size, "runtime bytecode")
+    };
+    let Some(limit) = limit else {
+        return Ok(());
+    };
+
+    let size = artifacts.bytecode.len();
+    if size <= limit {
+        return Ok(());
+    }
+
+    let mut blocks = contract
+        .blocks
+        .iter()
+        .map(|block| (&block.name, artifacts.blocks.get(&block.name).unwrap().size))
+        .collect::<Vec<_>>();
+    blocks.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+    let name_width = blocks.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    let mut sizes = "size of the blocks:".to_owned();
+    for (name, block_size) in blocks {
+        sizes.push_str(&format!(
+            "\n    {:<width$}  {:>6} bytes  ({:.1}%)",
+            name,
+            block_size,
+            100.0 * block_size as f64 / size as f64,
+            width = name_width
+        ));
+    }
+    let mut diagnostic = Diagnostic::new(
+        Code::ContractTooLarge,
+        format!(
+            "The {} of the contract `{}` is {} bytes long, which exceeds the limit of {} bytes by {} bytes.",
+            kind,
+            contract.name,
+            size,
+            limit,
+            size - limit
+        ),
+    )
+    .with_note(sizes);
+    if !contract.initcode {
+        diagnostic =
+            diagnostic.with_note("if this contract is meant to be deployed as initcode, mark it with `#[initcode]`");
+    }
+
+    Err(diagnostic)
+}
+
+/// Warns about the ABI entries whose selector (or topic for events) is never pushed in the contract.
+fn check_abi(contract: &Contract, warnings: &mut Diagnostics) {
+    if contract.allowed.contains(&Code::UnusedAbiEntry) {
+        return;
+    }
+
+    let mut pushed = HashSet::<Bytes32>::new();
+    for block in contract.blocks.iter().filter(|block| !block.data) {
+        let mut code = BytesMut::new();
+        for item in &block.items {
+            match &item.inner {
+                BlockItemInner::Bytes(bytes) | BlockItemInner::Data(bytes) => code.extend_from_slice(bytes),
+                inner => {
+                    pushed.extend(pushed_values(&code));
+                    code.clear();
+                    if let BlockItemInner::Push(Push {
+                        attributes: _,
+                        inner: PushInner::Constant(cst),
+                    }) = inner
+                    {
+                        pushed.insert(cst.clone());
+                    }
+                },
+            }
+        }
+        pushed.extend(pushed_values(&code));
+    }
+
+    for entry in &contract.abi {
+        let candidates = match entry {
+            AbiEntry::Function(function) => vec![right_aligned(&function.selector())],
+            AbiEntry::Error(error) => vec![right_aligned(&error.selector()), left_aligned(&error.selector())],
+            AbiEntry::Event(event) if !event.anonymous => vec![Bytes32(event.topic())],
+            AbiEntry::Event(_) => continue,
+        };
+
+        if !candidates.iter().any(|candidate| pushed.contains(candidate)) {
+            warnings.push(Diagnostic::new(
+                Code::UnusedAbiEntry,
+                format!(
+                    "The {} `{}` is declared in the ABI of the contract `{}`, but its {} is never pushed.",
+                    entry.kind(),
+                    entry.signature(),
+                    contract.name,
+                    if matches!(entry, AbiEntry::Event(_)) {
+                        "topic"
+                    } else {
+                        "selector"
+                    },
+                ),
+            ));
+        }
+    }
+}
+
+/// Values pushed by the push opcodes written as hex in some code - ex: `0x63a9059cbb`.
+fn pushed_values(code: &[u8]) -> Vec<Bytes32> {
+    let mut values = Vec::new();
+    let mut pc = 0;
+    while pc < code.len() {
+        let length = push_length(code[pc]).unwrap_or_default();
+        if length > 0 {
+            let value = Bytes::copy_from_slice(&code[pc + 1..(pc + 1 + length).min(code.len())]);
+            values.push(Bytes32::from_bytes(&value, true).unwrap());
+        }
+        pc += 1 + length;
+    }
+    values
+}
+
+fn right_aligned(selector: &[u8; 4]) -> Bytes32 {
+    Bytes32::from_bytes(&Bytes::copy_from_slice(selector), true).unwrap()
+}
+
+fn left_aligned(selector: &[u8; 4]) -> Bytes32 {
+    Bytes32::from_bytes(&Bytes::copy_from_slice(selector), false).unwrap()
+}
+
+/// Pushes a constant, and returns the annotation of the push when it is replaced by other opcodes. When
+/// optimizing, a constant with many leading `0xff` bytes - ex: a negative number - is pushed as its bitwise not,
+/// followed by `not`, if it is shorter.
+fn push_constant(
+    res: &mut BytesMut,
+    cst: &Bytes32,
+    assumes: &HashMap<Bytes32, u8>,
+    optimize: bool,
+    settings: &CompilerSettings,
+) -> Option<AnnotationKind> {
+    if settings.push0 && cst.is_zero() {
+        res.put_u8(PUSH0);
+        return Some(AnnotationKind::Push0);
+    } else if let Some(op) = assumes.get(cst) {
+        res.put_u8(*op);
+        return Some(AnnotationKind::Assumed(Bytes::copy_from_slice(cst.right_content())));
+    }
+
+    let complement = !cst;
+    if optimize && push_size(&complement, settings) + 1 < push_size(cst, settings) {
+        push_value(res, &complement, settings);
+        res.put_u8(NOT);
+        return Some(AnnotationKind::Not(Bytes::copy_from_slice(cst.right_content())));
+    }
+    push_value(res, cst, settings);
+    None
+}
+
+/// Size of the push of a constant.
+fn push_size(cst: &Bytes32, settings: &CompilerSettings) -> usize {
+    if cst.is_zero() {
+        if settings.push0 {
+            1
+        } else {
+            2
+        }
+    } else {
+        1 + cst.right_content().len()
+    }
+}
+
+fn push_value(res: &mut BytesMut, cst: &Bytes32, settings: &CompilerSettings) {
+    if settings.push0 && cst.is_zero() {
+        res.put_u8(PUSH0);
+    } else if cst.is_zero() {
+        res.put_u8(PUSH1);
+        res.put_u8(0x00);
+    } else {
+        let content = cst.right_content();
+        let push = PUSH0 + (content.len() as u8);
+        assert!(PUSH0 < push && push <= PUSH32);
+        res.put_u8(push);
+        res.extend_from_slice(content);
+    }
+}
+
+/// Address of a contract deployed with `create2` - `keccak256(0xff ++ deployer ++ salt ++ keccak256(initcode))[12..]`.
+fn create2_address(deployer: &[u8; 20], salt: &Bytes32, initcode: &[u8]) -> [u8; 20] {
+    let mut preimage = Vec::with_capacity(85);
+    preimage.push(0xff);
+    preimage.extend_from_slice(deployer);
+    preimage.extend_from_slice(&salt.0);
+    preimage.extend_from_slice(&keccak256(initcode));
 
-    artifacts
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&keccak256(&preimage)[12..]);
+    address
 }
 
 #[derive(Clone, Debug)]
@@ -52,57 +273,194 @@ enum Hole {
     Size(SizeHole),
 }
 
+/// Bytes emitted as is - raw bytes, or the bytecode of the contract `contract`.
+#[derive(Clone, Debug)]
+struct DataSegment {
+    pub block_index: usize,
+    pub start: usize,
+    pub end: usize,
+    pub contract: Option<usize>,
+}
+
 fn compile_contract(
-    blocks: &Vec<Block>,
+    contracts: &[Contract],
+    contract_index: usize,
     bytecodes: &HashMap<usize, Bytes>,
     settings: &CompilerSettings,
-) -> ContractArtifacts {
+    warnings: &mut Diagnostics,
+) -> Result<ContractArtifacts, Diagnostic> {
+    let blocks = &contracts[contract_index].blocks;
     let mut contract_artifacts = ContractArtifacts::default();
     let mut res = BytesMut::new();
+    let mut rng = match &settings.filling_pattern {
+        FillingPatern::Random { seed } => {
+            FillingRng::new(seed.expect("the seed is resolved from the source before the code generation"))
+        },
+        FillingPatern::Repeat(_) => FillingRng::new(0),
+    };
 
     let mut block_positions = HashMap::<usize, Vec<usize>>::new();
     let mut holes = Vec::<Hole>::new();
+    let mut data_segments = Vec::<DataSegment>::new();
+    let mut source_spans = Vec::<SourceSpan>::new();
+    let mut annotations = Vec::<Annotation>::new();
     let blocks_len = blocks.len();
     for block_index in 0..blocks_len {
         let block = &blocks[block_index];
+        if block.data && settings.data_guard && (block_index == 0 || !blocks[block_index - 1].data) {
+            source_spans.push(SourceSpan {
+                pc: res.len(),
+                location: None,
+            });
+            annotations.push(Annotation {
+                pc: res.len(),
+                kind: AnnotationKind::DataGuard,
+            });
+            res.put_u8(INVALID);
+        }
+
+        // only the data blocks are guarded, as the bytes of the other blocks are executed. The guard is put before
+        // the block, so that the block only holds its data
+        if block.data && settings.jumpdest_guard {
+            let mut data = BytesMut::new();
+            for item in &block.items {
+                match &item.inner {
+                    BlockItemInner::Bytes(bytes) | BlockItemInner::Data(bytes) => data.extend_from_slice(bytes),
+                    BlockItemInner::Contract(index) => data.extend_from_slice(bytecodes.get(index).unwrap()),
+                    BlockItemInner::Push(_) => (),
+                }
+            }
+            match jumpdest_guard(&data, pending_push_bytes(&res)) {
+                Ok(Some(guard)) => {
+                    source_spans.push(SourceSpan {
+                        pc: res.len(),
+                        location: None,
+                    });
+                    annotations.push(Annotation {
+                        pc: res.len(),
+                        kind: AnnotationKind::JumpdestGuard,
+                    });
+                    res.put_u8(guard);
+                },
+                Ok(None) => (),
+                Err(jumpdests) => {
+                    return Err(Diagnostic::new(
+                        Code::InvalidDataBlock,
+                        format!(
+                            "The data block `{}` of the contract `{}` has valid jump destinations at the offsets {} \
+                            which no push placed before it can cover.",
+                            block.name,
+                            contracts[contract_index].name,
+                            jumpdests
+                                .iter()
+                                .map(|offset| format!("0x{:04x}", offset))
+                                .collect::<Vec<_>>()
+                                .join(", "),
+                        ),
+                    )
+                    .with_note(
+                        "a guard covers at most the first 32 bytes of a data block, and is useless after a data block \
+                        which ends with an incomplete push",
+                    ));
+                },
+            }
+        }
+
         contract_artifacts.set_pc(&block.name, res.len());
+        annotations.push(Annotation {
+            pc: res.len(),
+            kind: AnnotationKind::Block(block.name.clone()),
+        });
 
         let mut pcs = Vec::with_capacity(block.items.len());
         for item in &block.items {
+            let data = match &item.inner {
+                BlockItemInner::Data(bytes) => Some((bytes, None)),
+                BlockItemInner::Contract(index) => Some((bytecodes.get(index).unwrap(), Some(*index))),
+                _ => None,
+            };
+
             for start_name in &item.start_names {
                 contract_artifacts.set_pc(start_name, res.len());
+                annotations.push(Annotation {
+                    pc: res.len(),
+                    kind: AnnotationKind::Block(start_name.clone()),
+                });
+            }
+            for abstract_name in &item.abstract_names {
+                annotations.push(Annotation {
+                    pc: res.len(),
+                    kind: AnnotationKind::Abstract(abstract_name.clone()),
+                });
             }
 
             pcs.push(res.len());
+            if item.sources.is_empty() {
+                source_spans.push(SourceSpan {
+                    pc: res.len(),
+                    location: None,
+                });
+            }
+            for source in &item.sources {
+                source_spans.push(SourceSpan {
+                    pc: res.len() + source.offset,
+                    location: Some(source.location.clone()),
+                });
+            }
+            if let Some((data, contract)) = data {
+                annotations.push(Annotation {
+                    pc: res.len(),
+                    kind: AnnotationKind::Data {
+                        size: data.len(),
+                        contract: contract.map(|index| contracts[index].name.clone()),
+                    },
+                });
+                data_segments.push(DataSegment {
+                    block_index,
+                    start: res.len(),
+                    end: res.len() + data.len(),
+                    contract,
+                });
+            }
             match &item.inner {
-                BlockItemInner::Bytes(bytes) => res.extend_from_slice(bytes),
-                BlockItemInner::Contract(contract_index) => {
-                    res.extend_from_slice(bytecodes.get(contract_index).unwrap())
+                BlockItemInner::Bytes(bytes) if block.data => {
+                    annotations.push(Annotation {
+                        pc: res.len(),
+                        kind: AnnotationKind::Data {
+                            size: bytes.len(),
+                            contract: None,
+                        },
+                    });
+                    res.extend_from_slice(bytes);
                 },
+                BlockItemInner::Bytes(bytes) | BlockItemInner::Data(bytes) => res.extend_from_slice(bytes),
+                BlockItemInner::Contract(index) => res.extend_from_slice(bytecodes.get(index).unwrap()),
                 BlockItemInner::Push(push) => {
                     let assumes: HashMap<Bytes32, u8> = if push.attributes.optimization {
                         push.attributes.assumes.iter().map(|(x, y)| (y.clone(), *x)).collect()
                     } else {
                         HashMap::new()
                     };
-                    match &push.inner {
-                        PushInner::Constant(cst) => {
-                            if settings.push0 && cst.is_zero() {
-                                res.put_u8(PUSH0);
-                            } else if let Some(op) = assumes.get(cst) {
-                                res.put_u8(*op);
-                            } else {
-                                if !settings.push0 && cst.is_zero() {
-                                    res.put_u8(PUSH1);
-                                    res.put_u8(0x00);
-                                } else {
-                                    let content = cst.right_content();
-                                    let push = PUSH0 + (content.len() as u8);
-                                    assert!(PUSH0 < push && push <= PUSH32);
-                                    res.put_u8(push);
-                                    res.extend_from_slice(content);
-                                }
-                            }
+                    let optimize = push.attributes.optimization;
+                    let pc = res.len();
+                    let annotation = match &push.inner {
+                        PushInner::Constant(cst) => push_constant(&mut res, cst, &assumes, optimize, settings),
+                        PushInner::ContractSize(contract_index) => {
+                            let size = bytecodes.get(contract_index).unwrap().len();
+                            push_constant(&mut res, &Bytes32::from_usize(size), &assumes, optimize, settings)
+                        },
+                        PushInner::ContractCodehash(contract_index) => {
+                            let codehash = keccak256(bytecodes.get(contract_index).unwrap());
+                            push_constant(&mut res, &Bytes32(codehash), &assumes, optimize, settings)
+                        },
+                        PushInner::Create2 {
+                            deployer,
+                            salt,
+                            index: contract_index,
+                        } => {
+                            let address = create2_address(deployer, salt, bytecodes.get(contract_index).unwrap());
+                            let address = Bytes32::from_bytes(&Bytes::copy_from_slice(&address), true).unwrap();
+                            push_constant(&mut res, &address, &assumes, optimize, settings)
                         },
                         PushInner::BlockSize { index, start, end } => {
                             res.put_u8(PUSH2);
@@ -114,6 +472,7 @@ fn compile_contract(
                             }));
                             res.put_u8(0x00);
                             res.put_u8(0x00);
+                            None
                         },
                         PushInner::BlockPc { index, line } => {
                             res.put_u8(PUSH2);
@@ -124,7 +483,11 @@ fn compile_contract(
                             }));
                             res.put_u8(0x00);
                             res.put_u8(0x00);
+                            None
                         },
+                    };
+                    if let Some(kind) = annotation {
+                        annotations.push(Annotation { pc, kind });
                     }
                 },
             }
@@ -135,7 +498,7 @@ fn compile_contract(
         }
         pcs.push(res.len());
 
-        if block_index != blocks_len - 1 {
+        if block_index != blocks_len - 1 && !block.data {
             let mut block_bytes_iter = res[pcs[0]..*pcs.last().unwrap()].iter();
 
             let mut bytes_to_add = 0;
@@ -152,8 +515,18 @@ fn compile_contract(
                 }
             }
 
+            source_spans.push(SourceSpan {
+                pc: res.len(),
+                location: None,
+            });
+            if bytes_to_add > 0 {
+                annotations.push(Annotation {
+                    pc: res.len(),
+                    kind: AnnotationKind::Filler(bytes_to_add),
+                });
+            }
             match &settings.filling_pattern {
-                FillingPatern::Random => fill_with_random(&mut res, bytes_to_add),
+                FillingPatern::Random { .. } => fill_with_random(&mut res, &mut rng, bytes_to_add),
                 FillingPatern::Repeat(pattern) => fill_with_pattern(&mut res, pattern, bytes_to_add),
             }
         }
@@ -185,6 +558,140 @@ fn compile_contract(
         }
     }
 
+    check_jumpdests(contracts, contract_index, &data_segments, &res, settings, warnings);
+
     contract_artifacts.bytecode = res.into();
-    contract_artifacts
+    contract_artifacts.source_spans = source_spans;
+    contract_artifacts.annotations = annotations;
+    Ok(contract_artifacts)
+}
+
+/// Performs the jumpdest analysis of the EVM on the bytecode, and warns about the valid jump destinations which
+/// come from raw bytes or from the bytecode of another contract, instead of a `jumpdest` opcode.
+fn check_jumpdests(
+    contracts: &[Contract],
+    contract_index: usize,
+    data_segments: &[DataSegment],
+    bytecode: &[u8],
+    settings: &CompilerSettings,
+    warnings: &mut Diagnostics,
+) {
+    let jumpdests = valid_jumpdests(bytecode);
+    for segment in data_segments {
+        let spurious = jumpdests
+            .iter()
+            .filter(|pc| segment.start <= **pc && **pc < segment.end)
+            .map(|pc| format!("0x{:04x}", pc))
+            .collect::<Vec<_>>();
+        let block = &contracts[contract_index].blocks[segment.block_index];
+        let allowed = contracts[contract_index].allowed.contains(&Code::SpuriousJumpdest)
+            || block.allowed.contains(&Code::SpuriousJumpdest);
+        if spurious.is_empty() || allowed {
+            continue;
+        }
+
+        let origin = match segment.contract {
+            Some(index) => format!("the bytecode of the contract `{}`", contracts[index].name),
+            None => "raw bytes".to_owned(),
+        };
+        let mut diagnostic = Diagnostic::new(
+            Code::SpuriousJumpdest,
+            format!(
+                "The contract `{}` has valid jump destinations at {} which come from {} in the block `{}`.",
+                contracts[contract_index].name,
+                spurious.join(", "),
+                origin,
+                block.name,
+            ),
+        );
+        if !block.data {
+            diagnostic = diagnostic.with_note("the setting `jumpdestGuard` only guards the data blocks");
+        } else if !settings.jumpdest_guard {
+            diagnostic = diagnostic.with_note("enable the setting `jumpdestGuard` to make them unreachable");
+        }
+        warnings.push(diagnostic);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compile::file::compile_source;
+    use crate::compile::settings::CompilerSettings;
+    use crate::diagnostics::codes::Code;
+
+    #[test]
+    pub fn test_jumpdest_guard() {
+        let settings = CompilerSettings {
+            jumpdest_guard: true,
+            ..Default::default()
+        };
+        let compile = |input: &str| {
+            let (artifacts, report) = compile_source("a.mep", input, "A", settings.clone()).unwrap();
+            let contract = &artifacts.contracts["A"];
+            let pcs = contract.blocks.values().map(|block| block.pc).collect::<Vec<_>>();
+            let notes = report
+                .diagnostics
+                .iter()
+                .flat_map(|warning| warning.notes.clone())
+                .collect::<Vec<_>>();
+            (hex::encode(&contract.bytecode), pcs, notes)
+        };
+
+        // the raw bytes of a code block are executed, so they are not guarded
+        let (bytecode, pcs, notes) = compile("contract A { block main { push(b.pc) jump } block b { 0x5b stop } }");
+        assert_eq!(bytecode, "610004565b00");
+        assert_eq!(pcs, [0, 4]);
+        assert_eq!(notes, ["the setting `jumpdestGuard` only guards the data blocks"]);
+
+        // a single guard, computed from the bytes of all the items, is put before the data block
+        let input = "\
+contract A {
+    block main { push(d.size) push(d.pc) push(0x) codecopy stop }
+    data block d { 0x5b &B.code }
+}
+
+contract B {
+    block main { jumpdest stop }
+}";
+        let (bytecode, pcs, notes) = compile(input);
+        assert_eq!(bytecode, "61000361000b5f3900fe615b5b00");
+        assert_eq!(pcs, [0, 11]);
+        assert!(notes.is_empty());
+
+        // a jump destination out of reach of a push, or after an incomplete push, cannot be guarded
+        let inputs = [
+            format!(
+                "contract A {{ block main {{ push(d.pc) stop }} data block d {{ 0x{}5b }} }}",
+                "00".repeat(32)
+            ),
+            "contract A { block main { push(d.pc) push(e.pc) stop } data block d { 0x60 } data block e { 0x005b } }"
+                .to_owned(),
+        ];
+        for input in inputs {
+            let report = compile_source("a.mep", &input, "A", settings.clone()).unwrap_err();
+            assert_eq!(report.diagnostics.iter().next().unwrap().code, Code::InvalidDataBlock);
+        }
+    }
+
+    #[test]
+    pub fn test_unused_abi_entry() {
+        let warnings = |block: &str| {
+            let input = format!(
+                "#[function(\"transfer(address,uint256)\")]\ncontract A {{ block main {{ {} }} }}",
+                block
+            );
+            let (_, report) = compile_source("a.mep", &input, "A", CompilerSettings::default()).unwrap();
+            report
+                .diagnostics
+                .iter()
+                .map(|diagnostic| diagnostic.code)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(warnings("stop"), [Code::UnusedAbiEntry]);
+        assert_eq!(warnings("push(0xa9059cbb) pop stop"), []);
+        // the selector is also found in the push opcodes written as hex
+        assert_eq!(warnings("0x63a9059cbb pop stop"), []);
+        assert_eq!(warnings("0x6300a9059cbb pop stop"), [Code::UnusedAbiEntry]);
+    }
 }
@@ -0,0 +1,173 @@
+use std::fmt;
+use std::path::Path;
+
+use bytes::Bytes;
+use indexmap::IndexMap;
+
+use super::artifacts::Artifacts;
+use super::file::{compile_source_contracts, declared_contracts, enabled_contracts};
+use super::settings::CompilerSettings;
+use crate::diagnostics::diagnostic::Diagnostic;
+use crate::diagnostics::report::Report;
+
+/// Compilation of in-memory sources - ex:
+/// `Compiler::new().source("a.mep", content).variable("OWNER", owner).contract("A").compile()`.
+#[derive(Debug, Clone, Default)]
+pub struct Compiler {
+    /// Content of the sources, by path. The paths are only used to select contracts and to report diagnostics.
+    sources: IndexMap<String, String>,
+    settings: CompilerSettings,
+    contracts: Vec<String>,
+}
+
+/// Contract compiled by a `Compiler`.
+#[derive(Debug, Clone)]
+pub struct CompiledContract {
+    /// Path of the source declaring the contract.
+    pub source: String,
+    pub name: String,
+    /// Artifacts of the contract, and of the contracts it uses.
+    pub artifacts: Artifacts,
+}
+
+/// Outcome of a compilation. When a report holds errors, the contracts of its source are not compiled.
+#[derive(Debug, Clone, Default)]
+pub struct Compilation {
+    pub contracts: Vec<CompiledContract>,
+    /// Diagnostics of the sources which have some, in the order of the sources.
+    pub reports: Vec<Report>,
+}
+
+/// Contract to compile which cannot be found in the sources.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompilerError {
+    /// No source declares the contract.
+    UnknownContract(String),
+    /// Many sources declare the contract, which must be selected as `path:Name`.
+    AmbiguousContract(String),
+}
+
+impl fmt::Display for CompilerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownContract(contract) => write!(f, "Contract `{}` not found in the sources.", contract),
+            Self::AmbiguousContract(contract) => write!(
+                f,
+                "Contract `{}` is declared in many sources, select it as `path:{}`.",
+                contract, contract
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CompilerError {}
+
+impl Compiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a source, or replaces the source with the same path.
+    pub fn source(mut self, path: impl Into<String>, content: impl Into<String>) -> Self {
+        self.sources.insert(path.into(), content.into());
+        self
+    }
+
+    pub fn settings(mut self, settings: CompilerSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Sets the value of a compile variable, over the variables of the settings.
+    pub fn variable(mut self, name: &str, value: impl Into<Bytes>) -> Self {
+        self.settings.add_variable(name, value.into());
+        self
+    }
+
+    /// Selects a contract to compile, as `Name` or as `path:Name` when many sources declare it. All the contracts
+    /// of the sources, but the ones disabled by a `cfg` attribute, are compiled when none is selected.
+    pub fn contract(mut self, contract: impl Into<String>) -> Self {
+        self.contracts.push(contract.into());
+        self
+    }
+
+    pub fn compile(&self) -> Result<Compilation, CompilerError> {
+        let mut res = Compilation::default();
+
+        let mut declared = Vec::<Vec<String>>::new();
+        for (path, content) in &self.sources {
+            match declared_contracts(path, content) {
+                Ok(contracts) => declared.push(contracts),
+                Err(report) => {
+                    declared.push(Vec::new());
+                    res.reports.push(report);
+                },
+            }
+        }
+        if res.has_errors() {
+            return Ok(res);
+        }
+
+        let mut selected = IndexMap::<usize, Vec<String>>::new();
+        if self.contracts.is_empty() {
+            for (source_index, (path, content)) in self.sources.iter().enumerate() {
+                let enabled = enabled_contracts(path, content, &self.settings).unwrap_or_default();
+                selected.insert(source_index, enabled);
+            }
+        }
+        for contract in &self.contracts {
+            let (path, name) = match contract.rsplit_once(':') {
+                Some((path, name)) => (Some(Path::new(path)), name),
+                None => (None, contract.as_str()),
+            };
+            let candidates = self
+                .sources
+                .keys()
+                .enumerate()
+                .filter(|(_, source)| path.is_none() || path == Some(Path::new(source)))
+                .filter(|(source_index, _)| declared[*source_index].iter().any(|declared| declared == name))
+                .map(|(source_index, _)| source_index)
+                .collect::<Vec<_>>();
+            match candidates.as_slice() {
+                [] => return Err(CompilerError::UnknownContract(contract.clone())),
+                [source_index] => selected.entry(*source_index).or_default().push(name.to_owned()),
+                _ => return Err(CompilerError::AmbiguousContract(contract.clone())),
+            }
+        }
+
+        for (source_index, contracts) in selected {
+            let (path, content) = self.sources.get_index(source_index).unwrap();
+            let (compiled, report) = compile_source_contracts(path, content, &contracts, &self.settings);
+            for artifacts in compiled {
+                res.contracts.push(CompiledContract {
+                    source: path.clone(),
+                    name: artifacts.main.clone(),
+                    artifacts,
+                });
+            }
+            if !report.diagnostics.is_empty() {
+                res.reports.push(report);
+            }
+        }
+        Ok(res)
+    }
+}
+
+impl Compilation {
+    pub fn has_errors(&self) -> bool {
+        self.reports.iter().any(Report::has_errors)
+    }
+
+    /// Artifacts of the compiled contract named `name`.
+    pub fn artifacts(&self, name: &str) -> Option<&Artifacts> {
+        self.contracts
+            .iter()
+            .find(|contract| contract.name == name)
+            .map(|contract| &contract.artifacts)
+    }
+
+    /// Errors and warnings of all the sources.
+    pub fn diagnostics(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.reports.iter().flat_map(|report| report.diagnostics.iter())
+    }
+}
@@ -1,30 +1,149 @@
-use super::artifacts::Artifacts;
+use super::artifacts::{Artifacts, BuildInfo};
 use super::compile::compile_contracts;
 use super::settings::CompilerSettings;
 use crate::ast::RFile;
+use crate::diagnostics::codes::Code;
+use crate::diagnostics::diagnostic::{Diagnostic, Diagnostics};
+use crate::diagnostics::report::Report;
+use crate::pre_processing::cfg::is_enabled;
 use crate::pre_processing::pre_processing::pre_process;
+use crate::types::hash::keccak256;
 
+/// Compiles the contract `contract_name` of a file. The error holds the rendered diagnostics of the compilation;
+/// use `compile_file_with_report` to get its warnings as well.
 pub fn compile_file(path: &str, contract_name: &str, settings: CompilerSettings) -> Result<Artifacts, String> {
-    let input = match std::fs::read_to_string(path) {
-        Ok(input) => input,
-        Err(err) => return Err(format!("Could not open file `{}`: {}", path, err.to_string())),
-    };
+    compile_file_with_report(path, contract_name, settings)
+        .map(|(artifacts, _)| artifacts)
+        .map_err(|report| report.to_string())
+}
+
+/// Compiles the contract `contract_name` of a file. The report holds the warnings, and the errors when the
+/// compilation fails.
+pub fn compile_file_with_report(
+    path: &str,
+    contract_name: &str,
+    settings: CompilerSettings,
+) -> Result<(Artifacts, Report), Report> {
+    let input = read_source(path)?;
+    compile_source(path, &input, contract_name, settings)
+}
+
+/// Compiles the contract `contract_name` of a source. `path` is only used to report the diagnostics.
+pub fn compile_source(
+    path: &str,
+    input: &str,
+    contract_name: &str,
+    mut settings: CompilerSettings,
+) -> Result<(Artifacts, Report), Report> {
+    settings.resolve_seed(input);
 
-    let r_file = match RFile::new(input.clone()) {
+    let r_file = match RFile::new(input.to_owned()) {
         Ok(r_file) => r_file,
-        Err(err) => {
-            return Err(format!("Parsing failed:\n{}", err));
-        },
+        Err(err) => return Err(Report::new(path, input, Diagnostic::from_pest(&err).into())),
     };
 
-    let pre_processed = match pre_process(&input, r_file, contract_name, &settings.variables) {
-        Ok(pre_processed) => pre_processed,
-        Err(err) => {
-            return Err(format!("Pre-processing failed:\n{}", err));
-        },
+    let build_info = BuildInfo {
+        compiler_version: env!("CARGO_PKG_VERSION").to_owned(),
+        settings: settings.clone(),
+        source_hash: keccak256(input.as_bytes()).to_vec().into(),
     };
+    let mut diagnostics = Diagnostics::default();
+    let result = pre_process(r_file, contract_name, &settings, &mut diagnostics).and_then(|pre_processed| {
+        compile_contracts(pre_processed, settings, &mut diagnostics).map_err(Diagnostics::from)
+    });
+
+    match result {
+        Ok(mut artifacts) => {
+            artifacts.build_info = Some(build_info);
+            Ok((artifacts, Report::new(path, input, diagnostics)))
+        },
+        Err(errors) => {
+            diagnostics.extend(errors);
+            Err(Report::new(path, input, diagnostics))
+        },
+    }
+}
+
+/// Compiles several contracts of a source, and returns the artifacts of the contracts which compiled. The
+/// diagnostics shared by the compilations are reported once, and a contract is only reported unused when none of
+/// the compiled contracts uses it.
+pub fn compile_source_contracts(
+    path: &str,
+    input: &str,
+    contract_names: &[String],
+    settings: &CompilerSettings,
+) -> (Vec<Artifacts>, Report) {
+    let mut compiled = Vec::<Artifacts>::new();
+    let mut reports = Vec::<Report>::new();
+    for contract_name in contract_names {
+        match compile_source(path, input, contract_name, settings.clone()) {
+            Ok((artifacts, report)) => {
+                compiled.push(artifacts);
+                reports.push(report);
+            },
+            Err(report) => reports.push(report),
+        }
+    }
 
-    let artifacts = compile_contracts(pre_processed, settings);
+    let mut diagnostics = Diagnostics::default();
+    for (index, report) in reports.iter().enumerate() {
+        for diagnostic in report.diagnostics.iter() {
+            let is_same = |other: &Diagnostic| {
+                other.code == diagnostic.code
+                    && other.location == diagnostic.location
+                    && other.message == diagnostic.message
+            };
+            if reports[..index]
+                .iter()
+                .any(|previous| previous.diagnostics.iter().any(is_same))
+            {
+                continue;
+            }
+            if diagnostic.code == Code::UnusedContract
+                && !reports.iter().all(|other| other.diagnostics.iter().any(is_same))
+            {
+                continue;
+            }
+            diagnostics.push(diagnostic.clone());
+        }
+    }
+
+    (compiled, Report::new(path, input, diagnostics))
+}
+
+/// Names of the contracts declared in a source, in the order of the source.
+pub fn declared_contracts(path: &str, input: &str) -> Result<Vec<String>, Report> {
+    match RFile::new(input.to_owned()) {
+        Ok(r_file) => Ok(r_file
+            .0
+            .iter()
+            .map(|r_contract| r_contract.inner().name_str().to_owned())
+            .collect()),
+        Err(err) => Err(Report::new(path, input, Diagnostic::from_pest(&err).into())),
+    }
+}
+
+/// Names of the contracts declared in a source which are not disabled by a `cfg` attribute with the compile
+/// variables of the settings. A contract whose condition is invalid is kept, its compilation reports the error.
+pub fn enabled_contracts(path: &str, input: &str, settings: &CompilerSettings) -> Result<Vec<String>, Report> {
+    match RFile::new(input.to_owned()) {
+        Ok(r_file) => Ok(r_file
+            .0
+            .iter()
+            .filter(|r_contract| is_enabled(&r_contract.attributes, &settings.variables).unwrap_or(true))
+            .map(|r_contract| r_contract.inner().name_str().to_owned())
+            .collect()),
+        Err(err) => Err(Report::new(path, input, Diagnostic::from_pest(&err).into())),
+    }
+}
 
-    Ok(artifacts)
+/// Content of a source file, or a report of why it could not be read.
+pub fn read_source(path: &str) -> Result<String, Report> {
+    std::fs::read_to_string(path).map_err(|err| {
+        let diagnostic = Diagnostic::new(
+            Code::UnreadableSource,
+            format!("Could not open file `{}`: {}", path, err),
+        );
+        Report::new(path, "", diagnostic.into())
+    })
 }
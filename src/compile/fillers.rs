@@ -2,10 +2,28 @@ use bytes::{BufMut, Bytes, BytesMut};
 
 use crate::pre_processing::opcode::push_length;
 
-pub fn fill_with_random(bytes: &mut BytesMut, n: usize) {
+/// Pseudo-random generator of the filling bytes (SplitMix64). Its output only depends on the seed, so that the
+/// bytecode is reproducible across machines and versions of the compiler.
+pub struct FillingRng(u64);
+
+impl FillingRng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+}
+
+pub fn fill_with_random(bytes: &mut BytesMut, rng: &mut FillingRng, n: usize) {
     for i in (0..n).rev() {
         bytes.put_u8(loop {
-            let op: u8 = rand::random();
+            let op = (rng.next_u64() >> 56) as u8;
             if let Some(push_length) = push_length(op) {
                 if push_length > i {
                     continue;
@@ -0,0 +1,104 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use bytes::{BufMut, Bytes, BytesMut};
+use serde_json::{json, Value};
+
+use super::artifacts::ContractArtifacts;
+use crate::abi::entry::AbiEntry;
+use crate::pre_processing::opcode::{CODECOPY, DUP1, PUSH0, PUSH1, RETURN};
+
+/// Artifact of a contract in the format of Foundry. The bytecode of a contract marked with `#[initcode]` is its
+/// deployment code, and its deployed bytecode is unknown. Otherwise, the deployment code is a wrapper which returns
+/// the bytecode.
+pub fn foundry_artifact(contract: &ContractArtifacts, push0: bool) -> Value {
+    let (bytecode, deployed_bytecode) = if contract.initcode {
+        (
+            bytecode_json(&contract.bytecode, &contract.source_map(0)),
+            bytecode_json(&[], ""),
+        )
+    } else {
+        (
+            bytecode_json(&deployment_code(&contract.bytecode, push0), ""),
+            bytecode_json(&contract.bytecode, &contract.source_map(0)),
+        )
+    };
+
+    let method_identifiers = contract
+        .abi
+        .iter()
+        .filter_map(|entry| match entry {
+            AbiEntry::Function(function) => Some((function.signature(), hex::encode(function.selector()))),
+            _ => None,
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    json!({
+        "abi": contract.abi,
+        "bytecode": bytecode,
+        "deployedBytecode": deployed_bytecode,
+        "methodIdentifiers": method_identifiers,
+    })
+}
+
+/// Path of the artifact of a contract in the layout of Foundry - `<out>/<File>.mep/<Contract>.json`.
+pub fn foundry_artifact_path(out_dir: &Path, source_path: &str, contract_name: &str) -> PathBuf {
+    let file_name = Path::new(source_path)
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| source_path.to_owned());
+    out_dir.join(file_name).join(format!("{}.json", contract_name))
+}
+
+/// Deployment code which copies `runtime` into memory and returns it:
+/// `push(size) dup1 push(offset) push(0x) codecopy push(0x) return`, followed by `runtime`.
+pub fn deployment_code(runtime: &[u8], push0: bool) -> Bytes {
+    let push = |res: &mut BytesMut, value: usize| {
+        let bytes = value.to_be_bytes();
+        let content = &bytes[bytes.iter().position(|byte| *byte != 0).unwrap_or(bytes.len())..];
+        if content.is_empty() && push0 {
+            res.put_u8(PUSH0);
+        } else {
+            let content = if content.is_empty() { &[0u8][..] } else { content };
+            res.put_u8(PUSH1 - 1 + content.len() as u8);
+            res.extend_from_slice(content);
+        }
+    };
+
+    // the offset of the runtime is the size of the wrapper, which depends on the length of the push of the offset
+    let mut offset = 0;
+    loop {
+        let mut res = BytesMut::new();
+        push(&mut res, runtime.len());
+        res.put_u8(DUP1);
+        push(&mut res, offset);
+        push(&mut res, 0);
+        res.put_u8(CODECOPY);
+        push(&mut res, 0);
+        res.put_u8(RETURN);
+        if res.len() == offset {
+            res.extend_from_slice(runtime);
+            return res.into();
+        }
+        offset = res.len();
+    }
+}
+
+fn bytecode_json(bytecode: &[u8], source_map: &str) -> Value {
+    json!({
+        "object": format!("0x{}", hex::encode(bytecode)),
+        "sourceMap": source_map,
+        "linkReferences": {},
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_deployment_code() {
+        assert_eq!(hex::encode(deployment_code(&[0x00], true)), "60018060095f395ff300");
+        assert_eq!(hex::encode(deployment_code(&[0x00], false)), "600180600b6000396000f300");
+    }
+}
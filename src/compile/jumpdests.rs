@@ -0,0 +1,50 @@
+use crate::pre_processing::opcode::{push_length, JUMPDEST, PUSH0, PUSH32};
+
+/// Positions of the valid jump destinations of a bytecode, as computed by the EVM: the `JUMPDEST` opcodes which
+/// are not part of the argument of a push.
+pub fn valid_jumpdests(bytecode: &[u8]) -> Vec<usize> {
+    jumpdests_after(bytecode, 0)
+}
+
+/// Number of bytes of the argument of a push which are missing at the end of a bytecode, and which will be taken
+/// from the bytes appended to it.
+pub fn pending_push_bytes(bytecode: &[u8]) -> usize {
+    let mut pc = 0;
+    while pc < bytecode.len() {
+        pc += 1 + push_length(bytecode[pc]).unwrap_or(0);
+    }
+    pc - bytecode.len()
+}
+
+/// Valid jump destinations of `data` when its first `skip` bytes are the argument of a push.
+fn jumpdests_after(data: &[u8], skip: usize) -> Vec<usize> {
+    let mut res = Vec::new();
+    let mut pc = skip;
+    while pc < data.len() {
+        let op = data[pc];
+        if op == JUMPDEST {
+            res.push(pc);
+        }
+        pc += 1 + push_length(op).unwrap_or(0);
+    }
+    res
+}
+
+/// Shortest push opcode which, placed before `data`, leaves no valid jump destination in `data`, when the bytes
+/// before it leave `pending` bytes of the argument of a push. Returns `Ok(None)` if no guard is needed, and the
+/// valid jump destinations of `data` if no push can cover them: they are more than 32 bytes in, or the guard
+/// would be part of the argument of the previous push.
+pub fn jumpdest_guard(data: &[u8], pending: usize) -> Result<Option<u8>, Vec<usize>> {
+    let jumpdests = jumpdests_after(data, pending);
+    if jumpdests.is_empty() {
+        return Ok(None);
+    }
+    if pending > 0 {
+        return Err(jumpdests);
+    }
+
+    (1..=data.len().min((PUSH32 - PUSH0) as usize))
+        .find(|n| jumpdests_after(data, *n).is_empty())
+        .map(|n| Some(PUSH0 + n as u8))
+        .ok_or(jumpdests)
+}
@@ -0,0 +1,89 @@
+use super::artifacts::{Annotation, AnnotationKind, ContractArtifacts};
+use crate::pre_processing::opcode::{op_to_str, push_length};
+
+/// Bytes listed per line in the data and the fillers.
+const DATA_LINE_SIZE: usize = 16;
+
+/// Assembly listing of the bytecode of a contract: one line per instruction with its pc, its bytes, its mnemonic
+/// and its immediate, under the blocks and the copies of abstract blocks it belongs to. The data and the fillers are
+/// listed as raw bytes, and the bytes changed or added by the compiler are commented.
+pub fn assembly_listing(contract: &ContractArtifacts) -> String {
+    let bytecode = &contract.bytecode;
+    let mut lines = Vec::<String>::new();
+    let mut annotations = contract.annotations.iter().peekable();
+    // bytes which are not instructions, until `region.0`
+    let mut region = (0, "");
+    let mut pc = 0;
+    while pc < bytecode.len() {
+        let mut notes = Vec::<String>::new();
+        while let Some(annotation) = annotations.next_if(|annotation| annotation.pc <= pc) {
+            annotate(annotation, &mut lines, &mut notes, &mut region);
+        }
+
+        let (size, asm) = if pc < region.0 {
+            (region.0.min(pc + DATA_LINE_SIZE) - pc, region.1.to_owned())
+        } else {
+            let op = bytecode[pc];
+            let immediate_length = push_length(op).unwrap_or_default();
+            let size = (1 + immediate_length).min(bytecode.len() - pc);
+            if size <= immediate_length {
+                notes.push("truncated push".to_owned());
+            }
+            let asm = match op_to_str(op) {
+                Some(name) if immediate_length > 0 => {
+                    format!("{} 0x{}", name, hex::encode(&bytecode[pc + 1..pc + size]))
+                },
+                Some(name) => name,
+                None => format!("unknown 0x{:02x}", op),
+            };
+            (size, asm)
+        };
+        while let Some(annotation) = annotations.next_if(|annotation| annotation.pc < pc + size) {
+            annotate(annotation, &mut lines, &mut notes, &mut region);
+        }
+
+        let mut line = format!("{:04x}  {:<32}  {}", pc, hex::encode(&bytecode[pc..pc + size]), asm);
+        if !notes.is_empty() {
+            line = format!("{:<64}  ; {}", line, notes.join(", "));
+        }
+        lines.push(line);
+        pc += size;
+    }
+
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+/// Adds the header lines of an annotation before the current line, or its notes on the current line.
+fn annotate(
+    annotation: &Annotation,
+    lines: &mut Vec<String>,
+    notes: &mut Vec<String>,
+    region: &mut (usize, &'static str),
+) {
+    match &annotation.kind {
+        AnnotationKind::Block(name) => {
+            if !lines.is_empty() {
+                lines.push(String::new());
+            }
+            lines.push(format!("{}:", name));
+        },
+        AnnotationKind::Abstract(name) => lines.push(format!("; copy of the abstract block `{}`", name)),
+        AnnotationKind::Data { size, contract } => {
+            *region = (annotation.pc + size, "data");
+            notes.push(match contract {
+                Some(contract) => format!("bytecode of `{}`", contract),
+                None => "raw bytes".to_owned(),
+            });
+        },
+        AnnotationKind::Push0 => notes.push("push of 0 as push0".to_owned()),
+        AnnotationKind::Assumed(value) => notes.push(format!("push of 0x{} as an assumed opcode", hex::encode(value))),
+        AnnotationKind::Not(value) => notes.push(format!("push of 0x{} as its bitwise not", hex::encode(value))),
+        AnnotationKind::Filler(size) => {
+            *region = (annotation.pc + size, "filler");
+            notes.push("completes the push of the block".to_owned());
+        },
+        AnnotationKind::DataGuard => notes.push("data guard".to_owned()),
+        AnnotationKind::JumpdestGuard => notes.push("jumpdest guard".to_owned()),
+    }
+}
@@ -1,5 +1,10 @@
 pub mod artifacts;
 pub mod compile;
+pub mod compiler;
 pub mod file;
 mod fillers;
+pub mod foundry;
+mod jumpdests;
+pub mod listing;
 pub mod settings;
+pub mod standard_json;
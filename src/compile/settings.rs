@@ -4,7 +4,15 @@ use bytes::Bytes;
 use serde::ser::SerializeMap;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::types::hash::keccak256;
+
+/// Maximum size of the runtime bytecode of a contract (EIP-170).
+pub const MAX_RUNTIME_SIZE: usize = 0x6000;
+
+/// Maximum size of the initcode of a contract (EIP-3860).
+pub const MAX_INITCODE_SIZE: usize = 2 * MAX_RUNTIME_SIZE;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(deny_unknown_fields, rename_all = "camelCase")]
 pub struct CompilerSettings {
     #[serde(default = "bool_true")]
@@ -17,6 +25,22 @@ pub struct CompilerSettings {
         deserialize_with = "deserialize_variables"
     )]
     pub variables: HashMap<String, Bytes>,
+    #[serde(default)]
+    pub dispatcher: DispatchStrategy,
+    /// Maximum size of the bytecode of a contract, `None` to disable the check.
+    #[serde(default = "max_runtime_size")]
+    pub max_runtime_size: Option<usize>,
+    /// Maximum size of the bytecode of a contract marked with `#[initcode]`, `None` to disable the check.
+    #[serde(default = "max_initcode_size")]
+    pub max_initcode_size: Option<usize>,
+    /// Puts a push before each data block which contains valid jump destinations, so that they become the argument
+    /// of the push, and fails when no push can cover them. The bytes of the other blocks are executed, so they are
+    /// never guarded.
+    #[serde(default)]
+    pub jumpdest_guard: bool,
+    /// Puts an `invalid` opcode between the code and the data blocks.
+    #[serde(default = "bool_true")]
+    pub data_guard: bool,
 }
 
 impl Default for CompilerSettings {
@@ -25,6 +49,11 @@ impl Default for CompilerSettings {
             push0: true,
             filling_pattern: FillingPatern::default(),
             variables: HashMap::default(),
+            dispatcher: DispatchStrategy::default(),
+            max_runtime_size: max_runtime_size(),
+            max_initcode_size: max_initcode_size(),
+            jumpdest_guard: false,
+            data_guard: true,
         }
     }
 }
@@ -33,22 +62,86 @@ impl CompilerSettings {
     pub fn add_variable(&mut self, name: &str, value: Bytes) {
         self.variables.insert(name.to_owned(), value);
     }
+
+    /// Derives the seed of the random filling from the hash of the source when none is given, so that a build is
+    /// reproducible. Every compilation of a source goes through it before the code generation.
+    pub(crate) fn resolve_seed(&mut self, input: &str) {
+        if let FillingPatern::Random { seed: seed @ None } = &mut self.filling_pattern {
+            let hash = keccak256(input.as_bytes());
+            seed.replace(u64::from_be_bytes(hash[..8].try_into().unwrap()));
+        }
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
 #[serde(deny_unknown_fields, rename_all = "camelCase")]
 pub enum FillingPatern {
-    Random,
-    #[serde(serialize_with = "serialize_bytes", deserialize_with = "deserialize_bytes")]
+    /// Pseudo-random bytes. Without seed, the seed is derived from the hash of the source.
+    Random {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        seed: Option<u64>,
+    },
+    #[serde(serialize_with = "serialize_bytes")]
     Repeat(Bytes),
 }
 
+/// Also accepts `"random"`, for the settings written before the seed was introduced.
+impl<'de> Deserialize<'de> for FillingPatern {
+    fn deserialize<D>(d: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields, rename_all = "camelCase")]
+        enum Tagged {
+            Random {
+                #[serde(default)]
+                seed: Option<u64>,
+            },
+            #[serde(deserialize_with = "deserialize_bytes")]
+            Repeat(Bytes),
+        }
+
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        enum Unit {
+            Random,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Unit(Unit),
+            Tagged(Tagged),
+        }
+
+        Ok(match Repr::deserialize(d)? {
+            Repr::Unit(Unit::Random) => Self::Random { seed: None },
+            Repr::Tagged(Tagged::Random { seed }) => Self::Random { seed },
+            Repr::Tagged(Tagged::Repeat(pattern)) => Self::Repeat(pattern),
+        })
+    }
+}
+
 impl Default for FillingPatern {
     fn default() -> Self {
         Self::Repeat(vec![0x00u8].into())
     }
 }
 
+/// Code generated for the block marked with `#[dispatch]`.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub enum DispatchStrategy {
+    /// Compares the selector against every entry, in declaration order.
+    #[default]
+    Linear,
+    /// Splits the sorted selectors in halves until a few remain.
+    BinarySearch,
+    /// Jumps through a table indexed by the lowest bits of the selector.
+    JumpTable,
+}
+
 pub fn serialize_bytes<S, T>(x: T, s: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
@@ -75,8 +168,10 @@ fn serialize_variables<S>(x: &HashMap<String, Bytes>, s: S) -> Result<S::Ok, S::
 where
     S: Serializer,
 {
+    let mut variables = x.iter().collect::<Vec<_>>();
+    variables.sort();
     let mut map = s.serialize_map(Some(x.len()))?;
-    for (k, v) in x.iter() {
+    for (k, v) in variables {
         map.serialize_entry(k, &format!("0x{}", hex::encode(v)))?;
     }
     map.end()
@@ -116,3 +211,11 @@ mod tests {
 fn bool_true() -> bool {
     true
 }
+
+fn max_runtime_size() -> Option<usize> {
+    Some(MAX_RUNTIME_SIZE)
+}
+
+fn max_initcode_size() -> Option<usize> {
+    Some(MAX_INITCODE_SIZE)
+}
@@ -0,0 +1,248 @@
+use std::collections::BTreeMap;
+
+use bytes::Bytes;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use super::file::{compile_source_contracts, enabled_contracts};
+use super::foundry::deployment_code;
+use super::settings::CompilerSettings;
+use crate::diagnostics::codes::Code;
+use crate::diagnostics::diagnostic::{Diagnostic, Severity};
+use crate::diagnostics::report::Report;
+
+/// Input of the Standard JSON mode, modeled after the one of solc.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+struct StandardJsonInput {
+    language: String,
+    sources: BTreeMap<String, StandardJsonSource>,
+    #[serde(default)]
+    settings: Map<String, Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StandardJsonSource {
+    content: Option<String>,
+}
+
+/// Outputs of a contract which can be selected.
+const OUTPUTS: &[&str] = &[
+    "abi",
+    "blocks",
+    "evm.bytecode.object",
+    "evm.bytecode.sourceMap",
+    "evm.deployedBytecode.object",
+    "evm.deployedBytecode.sourceMap",
+];
+
+/// Compiles the sources of a Standard JSON input, and returns the Standard JSON output: the selected outputs of
+/// each contract, and the errors and warnings. The settings are the compiler settings, with the additional field
+/// `outputSelection`.
+pub fn compile_standard_json(input: &str) -> Value {
+    let input: StandardJsonInput = match serde_json::from_str(input) {
+        Ok(input) => input,
+        Err(err) => return input_error(&format!("Invalid input: {}", err)),
+    };
+    if !input.language.eq_ignore_ascii_case("meplang") {
+        return input_error(&format!(
+            "Only the language `Meplang` is supported, got `{}`.",
+            input.language
+        ));
+    }
+
+    let mut settings = input.settings;
+    let output_selection = settings
+        .remove("outputSelection")
+        .unwrap_or_else(|| json!({"*": {"*": ["*"]}}));
+    let output_selection: BTreeMap<String, BTreeMap<String, Vec<String>>> =
+        match serde_json::from_value(output_selection) {
+            Ok(output_selection) => output_selection,
+            Err(err) => return input_error(&format!("Invalid output selection: {}", err)),
+        };
+    let settings: CompilerSettings = match serde_json::from_value(Value::Object(settings)) {
+        Ok(settings) => settings,
+        Err(err) => return input_error(&format!("Invalid settings: {}", err)),
+    };
+
+    let mut errors = Vec::<Value>::new();
+    let mut sources = Map::new();
+    let mut contracts = Map::new();
+    for (file_index, (name, source)) in input.sources.iter().enumerate() {
+        sources.insert(name.clone(), json!({ "id": file_index }));
+        let Some(content) = &source.content else {
+            return input_error(&format!(
+                "The source `{}` has no content, only inline sources are supported.",
+                name
+            ));
+        };
+
+        let declared = match enabled_contracts(name, content, &settings) {
+            Ok(declared) => declared,
+            Err(report) => {
+                errors.extend(
+                    report
+                        .diagnostics
+                        .iter()
+                        .map(|diagnostic| error_json(&report, diagnostic)),
+                );
+                continue;
+            },
+        };
+        let selected = declared
+            .into_iter()
+            .filter(|contract| !selected_outputs(&output_selection, name, contract).is_empty())
+            .collect::<Vec<_>>();
+        if selected.is_empty() {
+            continue;
+        }
+
+        let (compiled, report) = compile_source_contracts(name, content, &selected, &settings);
+        errors.extend(
+            report
+                .diagnostics
+                .iter()
+                .map(|diagnostic| error_json(&report, diagnostic)),
+        );
+
+        let mut file_contracts = Map::new();
+        for artifacts in compiled {
+            let contract = artifacts.main_artifacts();
+            let outputs = selected_outputs(&output_selection, name, &artifacts.main);
+            let mut res = Map::new();
+            // as in solc, `bytecode` is the creation code, so a contract which is not marked with `#[initcode]`
+            // is wrapped into a deployment code, and its own bytecode is the deployed bytecode
+            let ((creation_code, creation_map), (deployed_code, deployed_map)) = if contract.initcode {
+                (
+                    (contract.bytecode.clone(), contract.source_map(file_index)),
+                    (Bytes::new(), String::new()),
+                )
+            } else {
+                (
+                    (deployment_code(&contract.bytecode, settings.push0), String::new()),
+                    (contract.bytecode.clone(), contract.source_map(file_index)),
+                )
+            };
+            let mut bytecode = Map::new();
+            let mut deployed_bytecode = Map::new();
+            if outputs.contains(&"abi") {
+                res.insert("abi".to_owned(), serde_json::to_value(&contract.abi).unwrap());
+            }
+            if outputs.contains(&"blocks") {
+                res.insert("blocks".to_owned(), serde_json::to_value(&contract.blocks).unwrap());
+            }
+            if outputs.contains(&"evm.bytecode.object") {
+                bytecode.insert("object".to_owned(), hex::encode(&creation_code).into());
+            }
+            if outputs.contains(&"evm.bytecode.sourceMap") {
+                bytecode.insert("sourceMap".to_owned(), creation_map.into());
+            }
+            if outputs.contains(&"evm.deployedBytecode.object") {
+                deployed_bytecode.insert("object".to_owned(), hex::encode(&deployed_code).into());
+            }
+            if outputs.contains(&"evm.deployedBytecode.sourceMap") {
+                deployed_bytecode.insert("sourceMap".to_owned(), deployed_map.into());
+            }
+            let mut evm = Map::new();
+            if !bytecode.is_empty() {
+                evm.insert("bytecode".to_owned(), Value::Object(bytecode));
+            }
+            if !deployed_bytecode.is_empty() {
+                evm.insert("deployedBytecode".to_owned(), Value::Object(deployed_bytecode));
+            }
+            if !evm.is_empty() {
+                res.insert("evm".to_owned(), Value::Object(evm));
+            }
+            file_contracts.insert(artifacts.main.clone(), Value::Object(res));
+        }
+        if !file_contracts.is_empty() {
+            contracts.insert(name.clone(), Value::Object(file_contracts));
+        }
+    }
+
+    json!({
+        "errors": errors,
+        "sources": sources,
+        "contracts": contracts,
+    })
+}
+
+/// Outputs selected for the contract `contract` of the file `file`. A file, a contract or an output can be
+/// selected with `*`, and an output with one of its prefixes - ex: `evm.bytecode`.
+fn selected_outputs(
+    output_selection: &BTreeMap<String, BTreeMap<String, Vec<String>>>,
+    file: &str,
+    contract: &str,
+) -> Vec<&'static str> {
+    let selectors = output_selection
+        .iter()
+        .filter(|(selected_file, _)| *selected_file == "*" || *selected_file == file)
+        .flat_map(|(_, contracts)| contracts.iter())
+        .filter(|(selected_contract, _)| *selected_contract == "*" || *selected_contract == contract)
+        .flat_map(|(_, outputs)| outputs.iter())
+        .collect::<Vec<_>>();
+
+    OUTPUTS
+        .iter()
+        .copied()
+        .filter(|output| {
+            selectors.iter().any(|selector| {
+                *selector == "*"
+                    || output
+                        .strip_prefix(selector.as_str())
+                        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+            })
+        })
+        .collect()
+}
+
+/// Error of the Standard JSON output.
+fn error_json(report: &Report, diagnostic: &Diagnostic) -> Value {
+    let kind = match (diagnostic.severity, diagnostic.code) {
+        (Severity::Warning, _) => "Warning",
+        (Severity::Error, Code::SyntaxError) => "ParserError",
+        (Severity::Error, Code::InternalError) => "InternalCompilerError",
+        (Severity::Error, _) => "Error",
+    };
+
+    let mut res = json!({
+        "type": kind,
+        "component": "general",
+        "severity": diagnostic.severity.as_str(),
+        "errorCode": diagnostic.code.as_str(),
+        "message": diagnostic.message,
+        "formattedMessage": report.render(diagnostic),
+    });
+    if let Some(location) = &diagnostic.location {
+        res["sourceLocation"] = json!({ "file": report.path, "start": location.start, "end": location.end });
+    }
+    if !diagnostic.labels.is_empty() {
+        res["secondarySourceLocations"] = diagnostic
+            .labels
+            .iter()
+            .map(|label| {
+                json!({
+                    "file": report.path,
+                    "start": label.location.start,
+                    "end": label.location.end,
+                    "message": label.message,
+                })
+            })
+            .collect();
+    }
+    res
+}
+
+/// Output of an invalid input, with no source compiled.
+fn input_error(message: &str) -> Value {
+    json!({
+        "errors": [{
+            "type": "JSONError",
+            "component": "general",
+            "severity": "error",
+            "message": message,
+            "formattedMessage": message,
+        }],
+    })
+}
@@ -0,0 +1,300 @@
+use super::diagnostic::Severity;
+
+/// Stable code of a diagnostic. The codes of the errors start with `E`, and the codes of the warnings with `W`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Code {
+    SyntaxError,
+    NameAlreadyUsed,
+    UnknownOpcode,
+    UnknownName,
+    InvalidAttribute,
+    DuplicateMarker,
+    MissingEntryPoint,
+    InvalidBlockReference,
+    RecursiveReference,
+    InvalidPush,
+    UnknownField,
+    MissingCompileVariable,
+    InvalidDataBlock,
+    EmptyBlock,
+    InvalidDispatch,
+    InvalidAbi,
+    ContractTooLarge,
+    InvalidConstantName,
+    UnreadableSource,
+    InternalError,
+    InvalidLiteral,
+    InvalidRepeat,
+    StackMismatch,
+    UnusedContract,
+    UnusedBlock,
+    UnusedAbiEntry,
+    SpuriousJumpdest,
+}
+
+impl Code {
+    pub const ALL: &'static [Code] = &[
+        Self::SyntaxError,
+        Self::NameAlreadyUsed,
+        Self::UnknownOpcode,
+        Self::UnknownName,
+        Self::InvalidAttribute,
+        Self::DuplicateMarker,
+        Self::MissingEntryPoint,
+        Self::InvalidBlockReference,
+        Self::RecursiveReference,
+        Self::InvalidPush,
+        Self::UnknownField,
+        Self::MissingCompileVariable,
+        Self::InvalidDataBlock,
+        Self::EmptyBlock,
+        Self::InvalidDispatch,
+        Self::InvalidAbi,
+        Self::ContractTooLarge,
+        Self::InvalidConstantName,
+        Self::UnreadableSource,
+        Self::InternalError,
+        Self::InvalidLiteral,
+        Self::InvalidRepeat,
+        Self::StackMismatch,
+        Self::UnusedContract,
+        Self::UnusedBlock,
+        Self::UnusedAbiEntry,
+        Self::SpuriousJumpdest,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::SyntaxError => "E0001",
+            Self::NameAlreadyUsed => "E0002",
+            Self::UnknownOpcode => "E0003",
+            Self::UnknownName => "E0004",
+            Self::InvalidAttribute => "E0005",
+            Self::DuplicateMarker => "E0006",
+            Self::MissingEntryPoint => "E0007",
+            Self::InvalidBlockReference => "E0008",
+            Self::RecursiveReference => "E0009",
+            Self::InvalidPush => "E0010",
+            Self::UnknownField => "E0011",
+            Self::MissingCompileVariable => "E0012",
+            Self::InvalidDataBlock => "E0013",
+            Self::EmptyBlock => "E0014",
+            Self::InvalidDispatch => "E0015",
+            Self::InvalidAbi => "E0016",
+            Self::ContractTooLarge => "E0017",
+            Self::InvalidConstantName => "E0018",
+            Self::UnreadableSource => "E0019",
+            Self::InternalError => "E0020",
+            Self::InvalidLiteral => "E0021",
+            Self::InvalidRepeat => "E0022",
+            Self::StackMismatch => "E0023",
+            Self::UnusedContract => "W0001",
+            Self::UnusedBlock => "W0002",
+            Self::UnusedAbiEntry => "W0003",
+            Self::SpuriousJumpdest => "W0004",
+        }
+    }
+
+    /// Code written as `E0003` or `W0001`, case insensitive.
+    pub fn parse(code: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|candidate| candidate.as_str().eq_ignore_ascii_case(code))
+    }
+
+    /// Name of a warning, used to allow it - ex: `#[allow(unused_block)]`.
+    pub fn lint_name(self) -> Option<&'static str> {
+        match self {
+            Self::UnusedContract => Some("unused_contract"),
+            Self::UnusedBlock => Some("unused_block"),
+            Self::UnusedAbiEntry => Some("unused_abi_entry"),
+            Self::SpuriousJumpdest => Some("spurious_jumpdest"),
+            _ => None,
+        }
+    }
+
+    /// Warning named `name`, or whose code is `name`.
+    pub fn parse_lint(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .filter(|code| code.lint_name().is_some())
+            .find(|code| code.lint_name() == Some(name) || code.as_str().eq_ignore_ascii_case(name))
+    }
+
+    pub fn severity(self) -> Severity {
+        if self.as_str().starts_with('W') {
+            Severity::Warning
+        } else {
+            Severity::Error
+        }
+    }
+
+    /// Short description of the diagnostic.
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::SyntaxError => "syntax error",
+            Self::NameAlreadyUsed => "name already used",
+            Self::UnknownOpcode => "unknown opcode",
+            Self::UnknownName => "unknown name",
+            Self::InvalidAttribute => "invalid attribute",
+            Self::DuplicateMarker => "block marker used twice",
+            Self::MissingEntryPoint => "missing entry point",
+            Self::InvalidBlockReference => "invalid block reference",
+            Self::RecursiveReference => "recursive reference",
+            Self::InvalidPush => "invalid push",
+            Self::UnknownField => "unknown field",
+            Self::MissingCompileVariable => "missing compile variable",
+            Self::InvalidDataBlock => "invalid data block",
+            Self::EmptyBlock => "empty block",
+            Self::InvalidDispatch => "invalid dispatch",
+            Self::InvalidAbi => "invalid ABI entry",
+            Self::ContractTooLarge => "contract too large",
+            Self::InvalidConstantName => "invalid constant name",
+            Self::UnreadableSource => "unreadable source",
+            Self::InternalError => "internal compiler error",
+            Self::InvalidLiteral => "invalid literal",
+            Self::InvalidRepeat => "invalid repetition",
+            Self::StackMismatch => "stack mismatch",
+            Self::UnusedContract => "unused contract",
+            Self::UnusedBlock => "unused block",
+            Self::UnusedAbiEntry => "unused ABI entry",
+            Self::SpuriousJumpdest => "spurious jump destination",
+        }
+    }
+
+    /// Long explanation of the diagnostic, printed by `meplang explain`.
+    pub fn explanation(self) -> &'static str {
+        match self {
+            Self::SyntaxError => {
+                "The source does not follow the grammar of Meplang, or a hex literal has an odd number of digits.\n\n\
+                block main { push(0x123) }  // error: `0x123` has an odd number of digits"
+            },
+            Self::NameAlreadyUsed => {
+                "Two contracts of a file, or two blocks or constants of a contract, have the same name. The names \
+                of the blocks and constants must also differ from the names of the contracts.\n\n\
+                const X = 0x01;\n\
+                const X = 0x02;  // error: `X` is already used"
+            },
+            Self::UnknownOpcode => {
+                "A name used as an item of a block, or in an `assume` attribute, is neither an opcode nor a \
+                constant of the contract.\n\n\
+                block main { puhs0 }  // error: `puhs0` is not an opcode"
+            },
+            Self::UnknownName => {
+                "A block, a contract or a constant is referred to, but it is not declared. Blocks and constants \
+                are looked up in the enclosing contract.\n\n\
+                block main { *helper }  // error: no block `helper` in this contract"
+            },
+            Self::InvalidAttribute => {
+                "An attribute is unknown, its argument is invalid, or it is not allowed where it is written: \
+                `main` or `keep` on a contract, `initcode` on a block, a block marker on an abstract block, `last` in \
+                a contract with data blocks, or another attribute than `cfg` on a constant.\n\n\
+                #[main]\n\
+                contract C { ... }  // error: `main` marks a block"
+            },
+            Self::DuplicateMarker => {
+                "The attributes `main`, `last`, `dispatch` and `fallback` can only mark one block per contract. A \
+                block named `main` counts as marked with `main`."
+            },
+            Self::MissingEntryPoint => {
+                "The contract to compile is not declared in the file or is disabled by a `cfg` attribute, or a \
+                contract has no block named or marked `main`."
+            },
+            Self::InvalidBlockReference => {
+                "A block is referred to in a way that does not match its kind: `*block` copies a block once and \
+                `&block` inlines an abstract block, a non-abstract block cannot be copied twice nor inside an \
+                abstract block, and a data block can only be referred to by its `pc` or its `size`.\n\n\
+                abstract block a { ... }\n\
+                block main { *a }  // error: use `&a` for an abstract block"
+            },
+            Self::RecursiveReference => {
+                "A block includes itself, directly or through other blocks, or a contract includes its own code, \
+                directly or through other contracts."
+            },
+            Self::InvalidPush => {
+                "The argument of a push is invalid: it exceeds 32 bytes, a left push (`lpush`) is given something \
+                else than a constant, a `create2` deployer exceeds 20 bytes or its salt 32 bytes, or the function \
+                is not `push`, `rpush` nor `lpush`."
+            },
+            Self::UnknownField => {
+                "A field of a block or a contract does not exist. Blocks have the fields `pc` and `size`, and \
+                contracts have the fields `size`, `codehash` and `code`."
+            },
+            Self::MissingCompileVariable => {
+                "A compile variable (`$NAME$`) is used, but it has no value in the compiler settings.\n\n\
+                meplang compile -c C -i c.mep -D NAME=0x01"
+            },
+            Self::InvalidDataBlock => {
+                "A data block only holds raw bytes: literals, constants, compile variables and the code of other \
+                contracts. Its items cannot have attributes, it can only be marked with `keep`, and it cannot be \
+                `main`. With the setting `jumpdestGuard`, the valid jump destinations of a data block must be \
+                covered by a single push placed before it: they must be in its first 32 bytes, and the previous data \
+                block must not end with an incomplete push."
+            },
+            Self::EmptyBlock => "A block must hold at least one item, unless it is generated by the dispatcher.",
+            Self::InvalidDispatch => {
+                "The dispatcher is misused: a selector is declared without a block marked `dispatch`, two blocks \
+                have the same selector, or a block reached through the dispatcher does not start with \
+                `jumpdest`."
+            },
+            Self::InvalidAbi => {
+                "The signature of a `selector`, `function`, `event` or `error` attribute is invalid, or two \
+                entries of the ABI of a contract have the same selector or topic."
+            },
+            Self::ContractTooLarge => {
+                "The bytecode of a contract exceeds 24,576 bytes (EIP-170), or 49,152 bytes when it is marked \
+                `#[initcode]` (EIP-3860). The limits are set by the compiler settings `maxRuntimeSize` and \
+                `maxInitcodeSize`."
+            },
+            Self::InvalidConstantName => {
+                "A constant is named like an opcode or a push function, so it could not be told apart from \
+                them.\n\n\
+                const add = 0x01;  // error: `add` is an opcode"
+            },
+            Self::UnreadableSource => "The source file could not be read.",
+            Self::InternalError => {
+                "The compiler panicked. This is a bug of the compiler: please report it with the source that \
+                triggers it."
+            },
+            Self::InvalidLiteral => {
+                "A decimal literal exceeds 32 bytes, or an address literal which mixes lowercase and uppercase \
+                letters does not match its EIP-55 checksum. An address written in a single case is not checked.\n\n\
+                push(0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045)  // checksum valid\n\
+                push(0xd8da6bf26964af9d7eed9e03e53415d37aa96045)  // not checked\n\
+                push(0xD8dA6BF26964aF9D7eEd9e03E53415D37aA96045)  // error: invalid checksum"
+            },
+            Self::InvalidRepeat => {
+                "A `repeat` is written more than 1024 times, a block holds more than 65536 items once its nested \
+                repetitions are unrolled, or the index of a `repeat` is named like a constant, an opcode or a \
+                function. The index of a nested `repeat` needs another name than the index of the outer one.\n\n\
+                repeat(4, i) { repeat(2, i) { ... } }  // error: `i` is already the index of the outer repeat"
+            },
+            Self::StackMismatch => {
+                "A block takes more items from the stack than it holds, or a block with a stack signature does not \
+                leave the items it declares. Only the blocks with a signature are checked, starting with the items \
+                it takes. The code after a jump or a halt is not checked, nor the code following bytes which are \
+                not opcodes.\n\n\
+                abstract block shr_by(in: 2, out: 1) { shr }\n\
+                block main(in: 0, out: 1) { push(0x01) &shr_by }  // error: `shr_by` takes 2 items, the stack holds 1"
+            },
+            Self::UnusedContract => {
+                "A contract of the file is not compiled, because the compiled contract does not use it."
+            },
+            Self::UnusedBlock => {
+                "A block is never referred to from the `main` block, so it is not included in the bytecode. Mark \
+                it with `#[keep]` to include it anyway."
+            },
+            Self::UnusedAbiEntry => {
+                "A function or an error is declared in the ABI of a contract, but its selector is never pushed, \
+                or an event is declared but its topic is never pushed. The contract is unlikely to implement it."
+            },
+            Self::SpuriousJumpdest => {
+                "Raw bytes, or the code of another contract, contain a `0x5b` byte that is a valid jump \
+                destination of the contract. The compiler setting `jumpdestGuard` puts a push before such bytes \
+                to make them unreachable."
+            },
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use pest::error::InputLocation;
+
+use super::codes::Code;
+use crate::parser::parser::{Located, Location, Rule};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+        }
+    }
+}
+
+/// Secondary span of a diagnostic, with a message explaining its relation to the primary span.
+#[derive(Clone, Debug)]
+pub struct Label {
+    pub location: Location,
+    pub message: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: Code,
+    pub message: String,
+    /// Primary span, if the diagnostic relates to a part of the source.
+    pub location: Option<Location>,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+    /// New diagnostic, whose severity is given by its code.
+    pub fn new(code: Code, message: impl Into<String>) -> Self {
+        Self {
+            severity: code.severity(),
+            code,
+            message: message.into(),
+            location: None,
+            labels: Vec::new(),
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn at<T>(self, located: &Located<T>) -> Self {
+        self.at_location(&located.location)
+    }
+
+    pub fn at_location(mut self, location: &Location) -> Self {
+        self.location.replace(location.clone());
+        self
+    }
+
+    pub fn with_label(mut self, location: &Location, message: impl Into<String>) -> Self {
+        self.labels.push(Label {
+            location: location.clone(),
+            message: message.into(),
+        });
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    /// Diagnostic of an error of the parser.
+    pub fn from_pest(error: &pest::error::Error<Rule>) -> Self {
+        let location = match error.location {
+            InputLocation::Pos(pos) => Location { start: pos, end: pos },
+            InputLocation::Span((start, end)) => Location { start, end },
+        };
+        Self::new(Code::SyntaxError, error.variant.message()).at_location(&location)
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    fn start(&self) -> usize {
+        self.location.as_ref().map(|location| location.start).unwrap_or(0)
+    }
+}
+
+/// Diagnostics of a file, kept sorted by location so that they are reported in the order of the source.
+#[derive(Clone, Debug, Default)]
+pub struct Diagnostics(Vec<Diagnostic>);
+
+impl Diagnostics {
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        let start = diagnostic.start();
+        let index = self.0.partition_point(|existing| existing.start() <= start);
+        self.0.insert(index, diagnostic);
+    }
+
+    pub fn extend(&mut self, diagnostics: Diagnostics) {
+        for diagnostic in diagnostics.0 {
+            self.push(diagnostic);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.0.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Diagnostic> {
+        self.0.iter_mut()
+    }
+}
+
+impl From<Diagnostic> for Diagnostics {
+    fn from(diagnostic: Diagnostic) -> Self {
+        Self(vec![diagnostic])
+    }
+}
+
+impl IntoIterator for Diagnostics {
+    type Item = Diagnostic;
+    type IntoIter = std::vec::IntoIter<Diagnostic>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
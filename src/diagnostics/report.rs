@@ -0,0 +1,192 @@
+use std::fmt;
+
+use serde_json::{json, Value};
+
+use super::diagnostic::{Diagnostic, Diagnostics, Severity};
+use crate::parser::parser::Location;
+
+/// Diagnostics of a source file, with the source they refer to.
+#[derive(Clone, Debug)]
+pub struct Report {
+    pub path: String,
+    pub source: String,
+    pub diagnostics: Diagnostics,
+}
+
+impl Report {
+    pub fn new(path: &str, source: &str, diagnostics: Diagnostics) -> Self {
+        Self {
+            path: path.to_owned(),
+            source: source.to_owned(),
+            diagnostics,
+        }
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics.iter().any(Diagnostic::is_error)
+    }
+
+    /// Turns the warnings into errors.
+    pub fn deny_warnings(&mut self) {
+        for diagnostic in self.diagnostics.iter_mut() {
+            if diagnostic.severity == Severity::Warning {
+                diagnostic.severity = Severity::Error;
+                diagnostic.notes.push("the warnings are denied".to_owned());
+            }
+        }
+    }
+
+    /// Human readable rendering of a diagnostic, with the lines of the source it refers to.
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        let mut res = format!(
+            "{}[{}]: {}",
+            diagnostic.severity.as_str(),
+            diagnostic.code.as_str(),
+            diagnostic.message
+        );
+
+        let mut spans = Vec::<(&Location, char, &str)>::new();
+        if let Some(location) = &diagnostic.location {
+            spans.push((location, '^', ""));
+        }
+        for label in &diagnostic.labels {
+            spans.push((&label.location, '-', &label.message));
+        }
+
+        let gutter = spans
+            .iter()
+            .map(|(location, _, _)| self.line_column(location.start).0.to_string().len())
+            .max()
+            .unwrap_or(0);
+        let pad = " ".repeat(gutter);
+
+        for (index, (location, marker, message)) in spans.iter().enumerate() {
+            let (line, column) = self.line_column(location.start);
+            let arrow = if index == 0 { "-->" } else { ":::" };
+            res.push_str(&format!("\n{}{} {}:{}:{}", pad, arrow, self.path, line, column));
+
+            let text = self.source.lines().nth(line - 1).unwrap_or_default();
+            let start = self.source[..location.start]
+                .chars()
+                .rev()
+                .take_while(|c| *c != '\n')
+                .count();
+            let rest = text.chars().count().saturating_sub(start);
+            let length = self.source[location.start..location.end.max(location.start)]
+                .chars()
+                .take_while(|c| *c != '\n')
+                .count()
+                .clamp(1, rest.max(1));
+            res.push_str(&format!("\n{} |", pad));
+            res.push_str(&format!("\n{:>gutter$} | {}", line, text));
+            res.push_str(&format!(
+                "\n{} | {}{} {}",
+                pad,
+                " ".repeat(start),
+                marker.to_string().repeat(length),
+                message
+            ));
+            res.truncate(res.trim_end().len());
+        }
+
+        for note in &diagnostic.notes {
+            res.push_str(&format!(
+                "\n{} = note: {}",
+                pad,
+                note.replace('\n', &format!("\n{}         ", pad))
+            ));
+        }
+        res
+    }
+
+    /// JSON representation of a diagnostic, for tools.
+    pub fn json(&self, diagnostic: &Diagnostic) -> Value {
+        let span = |location: &Location, primary: bool, label: Option<&str>| {
+            let (line, column) = self.line_column(location.start);
+            let (end_line, end_column) = self.line_column(location.end);
+            json!({
+                "file": self.path,
+                "start": location.start,
+                "end": location.end,
+                "line": line,
+                "column": column,
+                "endLine": end_line,
+                "endColumn": end_column,
+                "primary": primary,
+                "label": label,
+            })
+        };
+
+        let mut spans = Vec::<Value>::new();
+        if let Some(location) = &diagnostic.location {
+            spans.push(span(location, true, None));
+        }
+        for label in &diagnostic.labels {
+            spans.push(span(&label.location, false, Some(&label.message)));
+        }
+
+        json!({
+            "severity": diagnostic.severity.as_str(),
+            "code": diagnostic.code.as_str(),
+            "message": diagnostic.message,
+            "spans": spans,
+            "notes": diagnostic.notes,
+            "rendered": self.render(diagnostic),
+        })
+    }
+
+    /// Line and column of an offset of the source, both starting at 1. Columns are counted in characters.
+    fn line_column(&self, offset: usize) -> (usize, usize) {
+        let before = &self.source[..offset.min(self.source.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before.chars().rev().take_while(|c| *c != '\n').count() + 1;
+        (line, column)
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, diagnostic) in self.diagnostics.iter().enumerate() {
+            if index != 0 {
+                writeln!(f)?;
+                writeln!(f)?;
+            }
+            write!(f, "{}", self.render(diagnostic))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::codes::Code;
+
+    #[test]
+    pub fn test_render() {
+        let source = "contract A {\n    block main { stop }\n    block main { stop }\n}\n";
+        let diagnostic = Diagnostic::new(Code::NameAlreadyUsed, "Name `main` already used")
+            .at_location(&Location { start: 47, end: 51 })
+            .with_label(&Location { start: 23, end: 27 }, "first declared here")
+            .with_note("names must be unique");
+        let report = Report::new("a.mep", source, diagnostic.into());
+
+        let expected = "\
+error[E0002]: Name `main` already used
+ --> a.mep:3:11
+  |
+3 |     block main { stop }
+  |           ^^^^
+ ::: a.mep:2:11
+  |
+2 |     block main { stop }
+  |           ---- first declared here
+  = note: names must be unique";
+        assert_eq!(report.to_string(), expected);
+
+        let json = report.json(report.diagnostics.iter().next().unwrap());
+        assert_eq!(json["code"], "E0002");
+        assert_eq!(json["spans"][0]["line"], 3);
+        assert_eq!(json["spans"][1]["label"], "first declared here");
+    }
+}
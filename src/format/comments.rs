@@ -0,0 +1,60 @@
+/// Comment found between two tokens of the source.
+#[derive(Debug, Clone)]
+pub struct Comment {
+    pub text: String,
+    /// Number of line breaks between the previous token (or comment) and this comment.
+    pub newlines_before: usize,
+}
+
+impl Comment {
+    pub fn is_line_comment(&self) -> bool {
+        self.text.starts_with("//")
+    }
+}
+
+/// Content of the source between two tokens: only whitespaces and comments.
+#[derive(Debug, Clone, Default)]
+pub struct Gap {
+    pub comments: Vec<Comment>,
+    /// Number of line breaks between the last comment (or the previous token) and the next token.
+    pub newlines_after: usize,
+}
+
+pub fn scan_gap(text: &str) -> Gap {
+    let mut gap = Gap::default();
+    let mut newlines = 0;
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        if rest.starts_with("//") {
+            let end = rest.find('\n').unwrap_or(rest.len());
+            gap.comments.push(Comment {
+                text: rest[..end].trim_end().to_owned(),
+                newlines_before: newlines,
+            });
+            newlines = 0;
+            rest = &rest[end..];
+        } else if rest.starts_with("/*") {
+            let end = rest.find("*/").map(|index| index + 2).unwrap_or(rest.len());
+            gap.comments.push(Comment {
+                text: rest[..end].to_owned(),
+                newlines_before: newlines,
+            });
+            newlines = 0;
+            rest = &rest[end..];
+        } else {
+            if c == '\n' {
+                newlines += 1;
+            }
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+
+    gap.newlines_after = newlines;
+    gap
+}
+
+/// Whether the text between two tokens holds a comment.
+pub fn contains_comment(text: &str) -> bool {
+    text.contains("//") || text.contains("/*")
+}
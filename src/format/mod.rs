@@ -0,0 +1,2 @@
+mod comments;
+pub mod printer;
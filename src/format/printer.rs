@@ -0,0 +1,461 @@
+use pest::iterators::Pair;
+use pest::Parser;
+
+use super::comments::{contains_comment, scan_gap, Gap};
+use crate::ast::RFile;
+use crate::parser::error::new_generic_error;
+use crate::parser::parser::{MeplangParser, Rule};
+
+const INDENT: &str = "    ";
+
+#[derive(Debug, Clone)]
+pub struct FormatSettings {
+    /// Maximum width of a line of opcodes. Longer lines are wrapped between two items.
+    pub max_width: usize,
+}
+
+impl Default for FormatSettings {
+    fn default() -> Self {
+        Self { max_width: 120 }
+    }
+}
+
+/// Formats a Meplang source. The comments are kept, and the items of a block stay on the lines where they were
+/// written, unless a line gets longer than `max_width`.
+pub fn format_source(input: &str, settings: &FormatSettings) -> Result<String, pest::error::Error<Rule>> {
+    let mut pairs = MeplangParser::parse(Rule::file, input)?;
+    let Some(file) = pairs.next() else {
+        return Err(new_generic_error("invalid file".to_owned()));
+    };
+
+    let mut printer = Printer {
+        input,
+        max_width: settings.max_width,
+        lines: Vec::new(),
+        current: None,
+        indent: 0,
+        cursor: 0,
+    };
+    printer.file(file);
+    let res = printer.finish();
+
+    if RFile::new(res.clone()).is_err() {
+        return Err(new_generic_error("The formatted source cannot be parsed.".to_owned()));
+    }
+    Ok(res)
+}
+
+struct Printer<'a> {
+    input: &'a str,
+    max_width: usize,
+    lines: Vec<String>,
+    /// Line being written, indentation included.
+    current: Option<String>,
+    indent: usize,
+    /// End of the last printed token in the input.
+    cursor: usize,
+}
+
+impl Printer<'_> {
+    fn file(&mut self, file: Pair<Rule>) {
+        let mut first = true;
+        for child in file.into_inner() {
+            match child.as_rule() {
+                Rule::contract_decl_with_attr => {
+                    let gap = self.gap(child.as_span().start());
+                    let gap = self.trailing_comment(gap);
+                    if !first {
+                        self.blank_line();
+                    }
+                    self.comments(&gap, !first);
+                    self.decl_with_attr(child, &gap, !first);
+                    first = false;
+                },
+                Rule::EOI => {
+                    let gap = self.gap(self.input.len());
+                    self.comments(&gap, true);
+                },
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    /// A contract, a block or a constant, with its attributes. The comments of `gap` are already printed.
+    fn decl_with_attr(&mut self, decl_with_attr: Pair<Rule>, gap: &Gap, allow_blank: bool) {
+        let mut gap = gap.clone();
+        for (index, child) in decl_with_attr.into_inner().enumerate() {
+            if index != 0 {
+                gap = self.gap(child.as_span().start());
+                self.comments(&gap, false);
+            }
+            match child.as_rule() {
+                Rule::attribute => {
+                    self.token(
+                        canonical(self.input, child.clone()),
+                        &gap,
+                        false,
+                        allow_blank && index == 0,
+                    );
+                    self.cursor = child.as_span().end();
+                },
+                Rule::contract_decl | Rule::block_decl => self.decl(child, &gap, allow_blank && index == 0),
+                Rule::const_decl => {
+                    self.token(
+                        canonical(self.input, child.clone()),
+                        &gap,
+                        false,
+                        allow_blank && index == 0,
+                    );
+                    self.cursor = child.as_span().end();
+                },
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    /// A contract, a block or a repetition, with its items.
+    fn decl(&mut self, decl: Pair<Rule>, gap: &Gap, allow_blank: bool) {
+        let is_repeat = decl.as_rule() == Rule::repeat;
+        let mut header = Vec::<String>::new();
+        let mut inner = decl.into_inner();
+        for child in inner.by_ref() {
+            let gap = self.gap(child.as_span().start());
+            for comment in gap.comments {
+                self.new_line(&comment.text);
+            }
+            if child.as_rule() == Rule::stack_signature {
+                let signature = canonical(self.input, child.clone());
+                header.last_mut().unwrap().push_str(&signature);
+            } else if !is_repeat || matches!(child.as_rule(), Rule::hex_alias | Rule::variable) {
+                header.push(canonical(self.input, child.clone()));
+            }
+            self.cursor = child.as_span().end();
+            if child.as_rule() == Rule::open_brace {
+                break;
+            }
+        }
+        let header = if is_repeat {
+            format!("repeat({}) {{", header.join(", "))
+        } else {
+            header.join(" ")
+        };
+        self.token(header, gap, false, allow_blank);
+
+        self.indent += 1;
+        let mut first = true;
+        let mut previous_rule = Option::<Rule>::None;
+        for child in inner {
+            let gap = self.gap(child.as_span().start());
+            match child.as_rule() {
+                Rule::block_item_with_attr => {
+                    self.comments(&gap, !first);
+                    for (index, item) in child.clone().into_inner().enumerate() {
+                        let gap = if index == 0 {
+                            gap.clone()
+                        } else {
+                            let gap = self.gap(item.as_span().start());
+                            self.comments(&gap, true);
+                            gap
+                        };
+                        if item.as_rule() == Rule::repeat {
+                            self.decl(item, &gap, !first);
+                            // the next item starts a new line
+                            self.flush();
+                        } else {
+                            self.token(canonical(self.input, item.clone()), &gap, !first, !first);
+                            self.cursor = item.as_span().end();
+                        }
+                        first = false;
+                    }
+                },
+                Rule::block_decl_with_attr | Rule::const_decl_with_attr => {
+                    let gap = self.trailing_comment(gap);
+                    let is_block = child.as_rule() == Rule::block_decl_with_attr;
+                    if !first && (is_block || previous_rule != Some(Rule::const_decl_with_attr) || gap_has_blank(&gap))
+                    {
+                        self.blank_line();
+                    }
+                    self.comments(&gap, !first);
+                    previous_rule.replace(child.as_rule());
+                    self.decl_with_attr(child, &gap, !first);
+                    first = false;
+                },
+                Rule::close_brace => {
+                    self.comments(&gap, !first);
+                    self.indent -= 1;
+                    if first && gap.comments.is_empty() {
+                        if let Some(current) = self.current.as_mut() {
+                            current.push('}');
+                        }
+                    } else {
+                        self.new_line("}");
+                    }
+                    self.cursor = child.as_span().end();
+                },
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.flush();
+        while self.lines.last().is_some_and(String::is_empty) {
+            self.lines.pop();
+        }
+        let mut res = self.lines.join("\n");
+        res.push('\n');
+        res
+    }
+
+    /// Prints the first comment of a gap if it is written after the previous token, on the same line.
+    fn trailing_comment(&mut self, mut gap: Gap) -> Gap {
+        if gap.comments.first().is_some_and(|comment| comment.newlines_before == 0) && self.current.is_some() {
+            let comment = gap.comments.remove(0);
+            self.comments(
+                &Gap {
+                    comments: vec![comment],
+                    newlines_after: 0,
+                },
+                false,
+            );
+        }
+        gap
+    }
+
+    /// Prints the comments of a gap. A comment written after a token stays on its line.
+    fn comments(&mut self, gap: &Gap, allow_blank: bool) {
+        let mut allow_blank = allow_blank;
+        for comment in &gap.comments {
+            match self.current.as_mut() {
+                Some(current) if comment.newlines_before == 0 => {
+                    current.push(' ');
+                    current.push_str(&comment.text);
+                },
+                _ => {
+                    if comment.newlines_before >= 2 && allow_blank {
+                        self.blank_line();
+                    }
+                    self.new_line(&comment.text);
+                },
+            }
+            if comment.is_line_comment() {
+                self.flush();
+            }
+            allow_blank = true;
+        }
+    }
+
+    /// Prints a token after the comments of its gap. With `flow`, the token stays on the line of the previous
+    /// token if it was on the same line in the source, and if the line is short enough.
+    fn token(&mut self, text: String, gap: &Gap, flow: bool, allow_blank: bool) {
+        if gap.newlines_after >= 2 && (allow_blank || !gap.comments.is_empty()) {
+            self.blank_line();
+        }
+
+        let fits = self
+            .current
+            .as_ref()
+            .is_some_and(|current| current.len() + 1 + text.len() <= self.max_width);
+        if flow && gap.newlines_after == 0 && fits {
+            let current = self.current.as_mut().unwrap();
+            current.push(' ');
+            current.push_str(&text);
+        } else {
+            self.new_line(&text);
+        }
+    }
+
+    fn new_line(&mut self, text: &str) {
+        self.flush();
+        self.current = Some(format!("{}{}", INDENT.repeat(self.indent), text));
+    }
+
+    fn flush(&mut self) {
+        if let Some(current) = self.current.take() {
+            self.lines.push(current.trim_end().to_owned());
+        }
+    }
+
+    fn blank_line(&mut self) {
+        self.flush();
+        if self.lines.last().is_some_and(|line| !line.is_empty()) {
+            self.lines.push(String::new());
+        }
+    }
+
+    fn gap(&mut self, until: usize) -> Gap {
+        let gap = scan_gap(&self.input[self.cursor..until]);
+        self.cursor = until;
+        gap
+    }
+}
+
+fn gap_has_blank(gap: &Gap) -> bool {
+    gap.newlines_after >= 2 || gap.comments.first().is_some_and(|comment| comment.newlines_before >= 2)
+}
+
+/// Canonical text of a token. A token holding a comment is kept as written.
+fn canonical(input: &str, pair: Pair<Rule>) -> String {
+    if has_comment(input, &pair) {
+        return pair.as_str().to_owned();
+    }
+
+    let rule = pair.as_rule();
+    let source = pair.as_str().to_owned();
+    let children = pair.into_inner().collect::<Vec<_>>();
+    let child = |rule: Rule| children.iter().filter(move |child| child.as_rule() == rule);
+    match rule {
+        Rule::attribute => {
+            let name = child(Rule::variable).next().unwrap().as_str();
+            match child(Rule::attribute_arg).next() {
+                Some(arg) => format!("#[{}({})]", name, canonical(input, arg.clone())),
+                None => format!("#[{}]", name),
+            }
+        },
+        Rule::attribute_equality => format!(
+            "{} = {}",
+            child(Rule::variable).next().unwrap().as_str(),
+            canonical(input, child(Rule::attribute_equality_right).next().unwrap().clone())
+        ),
+        Rule::attribute_not => format!(
+            "not({})",
+            canonical(input, child(Rule::attribute_arg).next().unwrap().clone())
+        ),
+        Rule::attribute_comparison => format!(
+            "{} == {}",
+            child(Rule::variable).next().unwrap().as_str(),
+            canonical(input, child(Rule::literal).next().unwrap().clone())
+        ),
+        Rule::attribute_stack => {
+            let mut names = [Vec::<&str>::new(), Vec::<&str>::new()];
+            let mut side = 0;
+            for child in &children {
+                match child.as_rule() {
+                    Rule::arrow => side = 1,
+                    _ => names[side].extend(
+                        child
+                            .clone()
+                            .into_inner()
+                            .filter(|name| name.as_rule() == Rule::variable)
+                            .map(|name| name.as_str()),
+                    ),
+                }
+            }
+            let [inputs, outputs] = names.map(|names| names.join(", "));
+            format!("{} -> {}", inputs, outputs).trim().to_owned()
+        },
+        Rule::stack_signature => {
+            let counts = child(Rule::decimal_digits)
+                .map(|digits| digits.as_str())
+                .collect::<Vec<_>>();
+            format!("(in: {}, out: {})", counts[0], counts[1])
+        },
+        Rule::const_decl => format!(
+            "const {} = {};",
+            child(Rule::variable).next().unwrap().as_str(),
+            canonical(input, child(Rule::const_arg).next().unwrap().clone())
+        ),
+        Rule::function => format!(
+            "{}({})",
+            child(Rule::variable).next().unwrap().as_str(),
+            canonical(input, child(Rule::function_arg).next().unwrap().clone())
+        ),
+        Rule::create2_call => format!(
+            "create2({})",
+            children
+                .iter()
+                .filter(|child| matches!(child.as_rule(), Rule::hex_alias | Rule::variable))
+                .map(|child| canonical(input, child.clone()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Rule::arithmetic => children
+            .iter()
+            .map(|child| canonical(input, child.clone()))
+            .collect::<Vec<_>>()
+            .join(" "),
+        Rule::concatenation => child(Rule::hex_alias)
+            .map(|child| canonical(input, child.clone()))
+            .collect::<Vec<_>>()
+            .join(" @ "),
+        Rule::block_ref => children
+            .iter()
+            .map(|child| canonical(input, child.clone()))
+            .collect::<Vec<_>>()
+            .join(""),
+        Rule::string_literal => source,
+        Rule::negation => format!("-{}", canonical(input, child(Rule::hex_alias).next().unwrap().clone())),
+        Rule::decimal_literal => children
+            .iter()
+            .map(|child| child.as_str())
+            .collect::<Vec<_>>()
+            .join(" "),
+        _ if children.len() == 1 => canonical(input, children[0].clone()),
+        _ => source,
+    }
+}
+
+/// Whether a comment is written between the tokens of a pair.
+fn has_comment(input: &str, pair: &Pair<Rule>) -> bool {
+    let mut leaves = pair
+        .clone()
+        .into_inner()
+        .flatten()
+        .filter(|child| child.clone().into_inner().next().is_none())
+        .map(|child| (child.as_span().start(), child.as_span().end()))
+        .collect::<Vec<_>>();
+    leaves.sort();
+
+    let mut cursor = pair.as_span().start();
+    for (start, end) in leaves {
+        if start > cursor && contains_comment(&input[cursor..start]) {
+            return true;
+        }
+        cursor = cursor.max(end);
+    }
+    contains_comment(&input[cursor..pair.as_span().end()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_format_source() {
+        let input = "\
+// header
+#[assume(msize=0x00)] contract A{ // trailing
+const X=0x01;
+  block main {  push( X ) push(0x20) add
+      #[assume( msize = 0x20 )]  *b mul mul mul // comment
+
+
+      stop } // end of main
+  block b { jumpdest }
+}
+";
+        let expected = "\
+// header
+#[assume(msize = 0x00)]
+contract A { // trailing
+    const X = 0x01;
+
+    block main {
+        push(X) push(0x20) add
+        #[assume(msize = 0x20)] *b mul
+        mul mul // comment
+
+        stop
+    } // end of main
+
+    block b {
+        jumpdest
+    }
+}
+";
+        let settings = FormatSettings { max_width: 40 };
+        let formatted = format_source(input, &settings).unwrap();
+        assert_eq!(formatted, expected);
+        assert_eq!(format_source(&formatted, &settings).unwrap(), formatted);
+    }
+}
@@ -1,11 +1,34 @@
 #![doc = include_str!("../README.md")]
 
+mod abi;
 mod ast;
 mod compile;
+mod diagnostics;
+mod format;
+mod lsp;
 mod parser;
 mod pre_processing;
+mod project;
 mod types;
 
-pub use compile::artifacts::{Artifacts, BlockArtifacts, ContractArtifacts};
-pub use compile::file::compile_file;
-pub use compile::settings::{CompilerSettings, FillingPatern};
+pub use abi::entry::{AbiEntry, AbiError, AbiEvent, AbiFunction, AbiParam, StateMutability};
+pub use compile::artifacts::{
+    Annotation, AnnotationKind, ArtifactFormat, Artifacts, BlockArtifacts, BuildInfo, ContractArtifacts, SourceSpan,
+};
+pub use compile::compiler::{Compilation, CompiledContract, Compiler, CompilerError};
+pub use compile::file::{
+    compile_file, compile_file_with_report, compile_source, compile_source_contracts, declared_contracts,
+    enabled_contracts, read_source,
+};
+pub use compile::foundry::{deployment_code, foundry_artifact, foundry_artifact_path};
+pub use compile::listing::assembly_listing;
+pub use compile::settings::{CompilerSettings, DispatchStrategy, FillingPatern};
+pub use compile::standard_json::compile_standard_json;
+pub use diagnostics::codes::Code;
+pub use diagnostics::diagnostic::{Diagnostic, Diagnostics, Label, Severity};
+pub use diagnostics::report::Report;
+pub use format::printer::{format_source, FormatSettings};
+pub use lsp::server::run_lsp_server;
+pub use parser::parser::Location;
+pub use project::build::{build_project, project_files, BuildOutput, BuiltContract};
+pub use project::manifest::{EvmVersion, Manifest, ProjectConfig, Target, MANIFEST_FILE};
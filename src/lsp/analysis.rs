@@ -0,0 +1,185 @@
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use crate::ast::{RConstantArg, RFile};
+use crate::compile::artifacts::Artifacts;
+use crate::compile::compile::compile_contracts;
+use crate::compile::settings::CompilerSettings;
+use crate::diagnostics::codes::Code;
+use crate::diagnostics::diagnostic::{Diagnostic, Diagnostics};
+use crate::parser::parser::Location;
+use crate::pre_processing::pre_processing::{get_literal_value, pre_process};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Contract,
+    Block,
+    AbstractBlock,
+    DataBlock,
+    Constant,
+}
+
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// Index of the contract declaring the symbol, or of the contract itself.
+    pub contract_index: usize,
+    pub location: Location,
+    pub name_location: Location,
+    /// Value of a constant, as written in the source.
+    pub value: Option<String>,
+}
+
+/// Contracts, blocks and constants declared in a file.
+pub fn symbols(r_file: &RFile) -> Vec<Symbol> {
+    let mut res = Vec::new();
+    for (contract_index, r_contract_with_attr) in r_file.0.iter().enumerate() {
+        let r_contract = r_contract_with_attr.inner();
+        res.push(Symbol {
+            name: r_contract.name_str().to_owned(),
+            kind: SymbolKind::Contract,
+            contract_index,
+            location: r_contract_with_attr.location.clone(),
+            name_location: r_contract.name.location.clone(),
+            value: None,
+        });
+
+        for r_block_with_attr in &r_contract.blocks {
+            let r_block = r_block_with_attr.inner();
+            res.push(Symbol {
+                name: r_block.name_str().to_owned(),
+                kind: if r_block.abstr {
+                    SymbolKind::AbstractBlock
+                } else if r_block.data {
+                    SymbolKind::DataBlock
+                } else {
+                    SymbolKind::Block
+                },
+                contract_index,
+                location: r_block_with_attr.location.clone(),
+                name_location: r_block.name.location.clone(),
+                value: None,
+            });
+        }
+
+        for r_constant in &r_contract.constants {
+            res.push(Symbol {
+                name: r_constant.name_str().to_owned(),
+                kind: SymbolKind::Constant,
+                contract_index,
+                location: r_constant.location.clone(),
+                name_location: r_constant.name.location.clone(),
+                value: match &r_constant.value.inner {
+                    RConstantArg::Literal(literal) => get_literal_value(literal, &r_constant.value.location)
+                        .ok()
+                        .map(|value| format!("0x{}", hex::encode(value))),
+                    RConstantArg::CompileVariable(compile_variable) => Some(format!("${}$", compile_variable.as_str())),
+                    RConstantArg::Negation(_) => None,
+                },
+            });
+        }
+    }
+    res
+}
+
+/// Index of the contract declared around the offset `offset`.
+pub fn enclosing_contract(symbols: &[Symbol], offset: usize) -> Option<usize> {
+    symbols
+        .iter()
+        .find(|symbol| {
+            symbol.kind == SymbolKind::Contract && symbol.location.start <= offset && offset <= symbol.location.end
+        })
+        .map(|symbol| symbol.contract_index)
+}
+
+/// Symbol named `name`, as seen from the offset `offset`: the blocks and constants of the enclosing contract
+/// come first, then the contracts.
+pub fn resolve<'a>(symbols: &'a [Symbol], name: &str, offset: usize) -> Option<&'a Symbol> {
+    let contract_index = enclosing_contract(symbols, offset);
+    let mut candidates = symbols.iter().filter(|symbol| symbol.name == name);
+    candidates
+        .clone()
+        .find(|symbol| symbol.kind != SymbolKind::Contract && Some(symbol.contract_index) == contract_index)
+        .or_else(|| candidates.find(|symbol| symbol.kind == SymbolKind::Contract))
+}
+
+/// Errors and warnings of the parsing, of the pre-processing and of the compilation of each contract of the file.
+pub fn diagnostics(input: &str) -> Vec<Diagnostic> {
+    let r_file = match RFile::new(input.to_owned()) {
+        Ok(r_file) => r_file,
+        Err(err) => return vec![Diagnostic::from_pest(&err)],
+    };
+
+    let mut res = Vec::<Diagnostic>::new();
+    for r_contract_with_attr in &r_file.0 {
+        let r_contract = r_contract_with_attr.inner();
+        let mut diagnostics = Diagnostics::default();
+        compile(
+            input,
+            &r_file,
+            r_contract.name_str(),
+            &r_contract.name.location,
+            &mut diagnostics,
+        );
+        for diagnostic in diagnostics {
+            if !res.iter().any(|existing| {
+                existing.message == diagnostic.message
+                    && existing.location.as_ref().map(|location| location.start)
+                        == diagnostic.location.as_ref().map(|location| location.start)
+            }) {
+                res.push(diagnostic);
+            }
+        }
+    }
+    res
+}
+
+/// Compiles the contract `contract_name` of the source `input` with the default settings, and collects its errors
+/// and warnings. The diagnostics without location are reported at `location`.
+pub fn compile(
+    input: &str,
+    r_file: &RFile,
+    contract_name: &str,
+    location: &Location,
+    diagnostics: &mut Diagnostics,
+) -> Option<Artifacts> {
+    let mut collected = Diagnostics::default();
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let mut settings = CompilerSettings::default();
+        settings.resolve_seed(input);
+        let mut warnings = Diagnostics::default();
+        let result = pre_process(r_file.clone(), contract_name, &settings, &mut warnings)
+            .and_then(|contracts| compile_contracts(contracts, settings, &mut warnings).map_err(Diagnostics::from));
+        (result, warnings)
+    }));
+
+    let artifacts = match result {
+        Ok((result, warnings)) => {
+            collected.extend(warnings);
+            match result {
+                Ok(artifacts) => Some(artifacts),
+                Err(errors) => {
+                    collected.extend(errors);
+                    None
+                },
+            }
+        },
+        Err(_) => {
+            collected.push(Diagnostic::new(
+                Code::InternalError,
+                format!(
+                    "The compiler panicked while compiling the contract `{}`.",
+                    contract_name
+                ),
+            ));
+            None
+        },
+    };
+
+    for mut diagnostic in collected {
+        if diagnostic.location.is_none() {
+            diagnostic.location.replace(location.clone());
+        }
+        diagnostics.push(diagnostic);
+    }
+    artifacts
+}
@@ -0,0 +1,67 @@
+use serde_json::{json, Value};
+
+use crate::parser::parser::Location;
+
+/// Text of an open document. The positions of the protocol are given in UTF-16 code units.
+pub struct Document {
+    pub text: String,
+    line_starts: Vec<usize>,
+}
+
+impl Document {
+    pub fn new(text: String) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+        Self { text, line_starts }
+    }
+
+    pub fn position(&self, offset: usize) -> Value {
+        let offset = offset.min(self.text.len());
+        let line = self.line_starts.partition_point(|start| *start <= offset) - 1;
+        let character = self.text[self.line_starts[line]..offset]
+            .chars()
+            .map(char::len_utf16)
+            .sum::<usize>();
+        json!({ "line": line, "character": character })
+    }
+
+    pub fn range(&self, location: &Location) -> Value {
+        json!({ "start": self.position(location.start), "end": self.position(location.end) })
+    }
+
+    pub fn offset(&self, position: &Value) -> Option<usize> {
+        let line = position.get("line")?.as_u64()? as usize;
+        let character = position.get("character")?.as_u64()? as usize;
+
+        let start = *self.line_starts.get(line)?;
+        let end = self.line_starts.get(line + 1).copied().unwrap_or(self.text.len());
+        let mut units = 0;
+        for (index, c) in self.text[start..end].char_indices() {
+            if units >= character {
+                return Some(start + index);
+            }
+            units += c.len_utf16();
+        }
+        Some(end)
+    }
+
+    /// Identifier under the offset, with its location.
+    pub fn word_at(&self, offset: usize) -> Option<(&str, Location)> {
+        let is_word_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+        let start = self.text[..offset]
+            .rfind(|c: char| !is_word_char(c))
+            .map(|index| index + 1)
+            .unwrap_or(0);
+        let end = self.text[offset..]
+            .find(|c: char| !is_word_char(c))
+            .map(|index| offset + index)
+            .unwrap_or(self.text.len());
+
+        if start < end {
+            Some((&self.text[start..end], Location { start, end }))
+        } else {
+            None
+        }
+    }
+}
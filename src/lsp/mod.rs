@@ -0,0 +1,4 @@
+mod analysis;
+mod document;
+pub mod server;
+mod transport;
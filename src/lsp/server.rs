@@ -0,0 +1,297 @@
+use std::collections::HashMap;
+use std::io::Write;
+
+use serde_json::{json, Value};
+
+use super::analysis::{compile, diagnostics, enclosing_contract, resolve, symbols, Symbol, SymbolKind};
+use super::document::Document;
+use super::transport::{read_message, write_message};
+use crate::ast::RFile;
+use crate::diagnostics::diagnostic::{Diagnostics, Severity};
+use crate::parser::parser::Location;
+use crate::pre_processing::opcode::{str_to_op, OPCODE_NAMES};
+
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// Runs the language server over stdin and stdout until the client asks to exit.
+pub fn run_lsp_server() -> Result<(), String> {
+    let stdin = std::io::stdin();
+    let stdout = std::io::stdout();
+    let mut reader = stdin.lock();
+    let mut writer = stdout.lock();
+
+    let mut server = Server::default();
+    while let Some(message) = read_message(&mut reader)? {
+        let method = message.get("method").and_then(Value::as_str).unwrap_or_default();
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        let Some(id) = message.get("id").cloned() else {
+            if method == "exit" {
+                return Ok(());
+            }
+            server.notification(method, &params, &mut writer)?;
+            continue;
+        };
+
+        let response = match server.request(method, &params) {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": code, "message": message },
+            }),
+        };
+        write_message(&mut writer, &response)?;
+    }
+
+    Ok(())
+}
+
+#[derive(Default)]
+struct Server {
+    documents: HashMap<String, Document>,
+}
+
+impl Server {
+    fn request(&mut self, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+        Ok(match method {
+            "initialize" => json!({
+                "capabilities": {
+                    "textDocumentSync": 1,
+                    "definitionProvider": true,
+                    "hoverProvider": true,
+                    "completionProvider": {},
+                    "documentSymbolProvider": true,
+                },
+                "serverInfo": { "name": "meplang", "version": env!("CARGO_PKG_VERSION") },
+            }),
+            "shutdown" => Value::Null,
+            "textDocument/definition" => self.definition(params).unwrap_or(Value::Null),
+            "textDocument/hover" => self.hover(params).unwrap_or(Value::Null),
+            "textDocument/completion" => self.completion(params).unwrap_or(Value::Null),
+            "textDocument/documentSymbol" => self.document_symbols(params).unwrap_or(Value::Null),
+            _ => return Err((METHOD_NOT_FOUND, format!("Unhandled method `{}`.", method))),
+        })
+    }
+
+    fn notification(&mut self, method: &str, params: &Value, writer: &mut impl Write) -> Result<(), String> {
+        let Some(uri) = params.pointer("/textDocument/uri").and_then(Value::as_str) else {
+            return Ok(());
+        };
+
+        let text = match method {
+            "textDocument/didOpen" => params.pointer("/textDocument/text").and_then(Value::as_str),
+            "textDocument/didChange" => params
+                .get("contentChanges")
+                .and_then(Value::as_array)
+                .and_then(|changes| changes.last())
+                .and_then(|change| change.get("text"))
+                .and_then(Value::as_str),
+            "textDocument/didClose" => {
+                self.documents.remove(uri);
+                return publish_diagnostics(writer, uri, Vec::new());
+            },
+            _ => None,
+        };
+
+        if let Some(text) = text {
+            let document = Document::new(text.to_owned());
+            let diagnostics = diagnostics(&document.text)
+                .into_iter()
+                .map(|diagnostic| {
+                    let related = diagnostic
+                        .labels
+                        .iter()
+                        .map(|label| {
+                            json!({
+                                "location": { "uri": uri, "range": document.range(&label.location) },
+                                "message": label.message,
+                            })
+                        })
+                        .collect::<Vec<_>>();
+                    let mut message = diagnostic.message.clone();
+                    for note in &diagnostic.notes {
+                        message.push_str(&format!("\nnote: {}", note));
+                    }
+                    json!({
+                        "range": document.range(&diagnostic.location.clone().unwrap_or(Location { start: 0, end: 0 })),
+                        "severity": match diagnostic.severity {
+                            Severity::Error => 1,
+                            Severity::Warning => 2,
+                        },
+                        "code": diagnostic.code.as_str(),
+                        "source": "meplang",
+                        "message": message,
+                        "relatedInformation": related,
+                    })
+                })
+                .collect();
+            self.documents.insert(uri.to_owned(), document);
+            publish_diagnostics(writer, uri, diagnostics)?;
+        }
+        Ok(())
+    }
+
+    /// Document, parsed file, and offset of the position of a request.
+    fn locate<'a>(&'a self, params: &'a Value) -> Option<(&'a str, &'a Document, RFile, usize)> {
+        let uri = params.pointer("/textDocument/uri")?.as_str()?;
+        let document = self.documents.get(uri)?;
+        let offset = document.offset(params.get("position")?)?;
+        let r_file = RFile::new(document.text.clone()).ok()?;
+        Some((uri, document, r_file, offset))
+    }
+
+    fn definition(&self, params: &Value) -> Option<Value> {
+        let (uri, document, r_file, offset) = self.locate(params)?;
+        let (word, _) = document.word_at(offset)?;
+        let symbols = symbols(&r_file);
+        let symbol = resolve(&symbols, word, offset)?;
+        Some(json!({ "uri": uri, "range": document.range(&symbol.name_location) }))
+    }
+
+    fn hover(&self, params: &Value) -> Option<Value> {
+        let (_, document, r_file, offset) = self.locate(params)?;
+        let (word, location) = document.word_at(offset)?;
+        let symbols = symbols(&r_file);
+
+        let contents = match resolve(&symbols, word, offset) {
+            Some(symbol) => hover_symbol(&document.text, &r_file, &symbols, symbol),
+            None => {
+                let op = str_to_op(word)?;
+                format!("opcode `{}` (`0x{:02x}`)", word.to_lowercase(), op)
+            },
+        };
+
+        Some(json!({
+            "contents": { "kind": "markdown", "value": contents },
+            "range": document.range(&location),
+        }))
+    }
+
+    fn completion(&self, params: &Value) -> Option<Value> {
+        let uri = params.pointer("/textDocument/uri")?.as_str()?;
+        let document = self.documents.get(uri)?;
+        let offset = document.offset(params.get("position")?)?;
+
+        let mut items = OPCODE_NAMES
+            .iter()
+            .map(|name| json!({ "label": name, "kind": 14, "detail": format!("0x{:02x}", str_to_op(name).unwrap()) }))
+            .collect::<Vec<_>>();
+
+        // the names are only completed when the file parses
+        let r_file = RFile::new(document.text.clone()).unwrap_or_default();
+        let symbols = symbols(&r_file);
+        let contract_index = enclosing_contract(&symbols, offset);
+        for symbol in &symbols {
+            if symbol.kind == SymbolKind::Contract || Some(symbol.contract_index) == contract_index {
+                items.push(json!({
+                    "label": symbol.name,
+                    "kind": match symbol.kind {
+                        SymbolKind::Contract => 7,
+                        SymbolKind::Constant => 21,
+                        _ => 3,
+                    },
+                    "detail": symbol_kind_name(symbol.kind),
+                }));
+            }
+        }
+
+        Some(Value::Array(items))
+    }
+
+    fn document_symbols(&self, params: &Value) -> Option<Value> {
+        let uri = params.pointer("/textDocument/uri")?.as_str()?;
+        let document = self.documents.get(uri)?;
+        let r_file = RFile::new(document.text.clone()).ok()?;
+        let symbols = symbols(&r_file);
+
+        let document_symbol = |symbol: &Symbol, children: Vec<Value>| {
+            json!({
+                "name": symbol.name,
+                "detail": symbol_kind_name(symbol.kind),
+                "kind": match symbol.kind {
+                    SymbolKind::Contract => 5,
+                    SymbolKind::Constant => 14,
+                    _ => 12,
+                },
+                "range": document.range(&symbol.location),
+                "selectionRange": document.range(&symbol.name_location),
+                "children": children,
+            })
+        };
+
+        let contracts = symbols
+            .iter()
+            .filter(|symbol| symbol.kind == SymbolKind::Contract)
+            .map(|contract| {
+                let children = symbols
+                    .iter()
+                    .filter(|symbol| symbol.kind != SymbolKind::Contract)
+                    .filter(|symbol| symbol.contract_index == contract.contract_index)
+                    .map(|symbol| document_symbol(symbol, Vec::new()))
+                    .collect();
+                document_symbol(contract, children)
+            })
+            .collect();
+
+        Some(Value::Array(contracts))
+    }
+}
+
+fn hover_symbol(input: &str, r_file: &RFile, symbols: &[Symbol], symbol: &Symbol) -> String {
+    let kind = symbol_kind_name(symbol.kind);
+    if let Some(value) = &symbol.value {
+        return format!("{} `{}` = `{}`", kind, symbol.name, value);
+    }
+    if symbol.kind == SymbolKind::AbstractBlock {
+        return format!("{} `{}`", kind, symbol.name);
+    }
+
+    let contract = symbols
+        .iter()
+        .find(|contract| contract.kind == SymbolKind::Contract && contract.contract_index == symbol.contract_index)
+        .unwrap();
+    let compiled = match compile(
+        input,
+        r_file,
+        &contract.name,
+        &contract.name_location,
+        &mut Diagnostics::default(),
+    ) {
+        Some(artifacts) => {
+            let contract_artifacts = artifacts.contracts.get(&contract.name).unwrap();
+            if symbol.kind == SymbolKind::Contract {
+                format!("size: {} bytes", contract_artifacts.bytecode.len())
+            } else {
+                match contract_artifacts.blocks.get(&symbol.name) {
+                    Some(block) => format!("pc: `0x{:04x}`, size: {} bytes", block.pc, block.size),
+                    None => "not included in the bytecode".to_owned(),
+                }
+            }
+        },
+        None => "the contract does not compile".to_owned(),
+    };
+
+    format!("{} `{}`\n\n{}", kind, symbol.name, compiled)
+}
+
+fn symbol_kind_name(kind: SymbolKind) -> &'static str {
+    match kind {
+        SymbolKind::Contract => "contract",
+        SymbolKind::Block => "block",
+        SymbolKind::AbstractBlock => "abstract block",
+        SymbolKind::DataBlock => "data block",
+        SymbolKind::Constant => "const",
+    }
+}
+
+fn publish_diagnostics(writer: &mut impl Write, uri: &str, diagnostics: Vec<Value>) -> Result<(), String> {
+    write_message(
+        writer,
+        &json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": { "uri": uri, "diagnostics": diagnostics },
+        }),
+    )
+}
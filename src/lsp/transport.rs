@@ -0,0 +1,41 @@
+use std::io::{BufRead, Write};
+
+use serde_json::Value;
+
+/// Reads a JSON-RPC message, preceded by its `Content-Length` header. Returns `None` at the end of the input.
+pub fn read_message(reader: &mut impl BufRead) -> Result<Option<Value>, String> {
+    let mut content_length = Option::<usize>::None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).map_err(|err| err.to_string())? == 0 {
+            return Ok(None);
+        }
+
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                let length = value.trim().parse::<usize>().map_err(|err| err.to_string())?;
+                content_length.replace(length);
+            }
+        }
+    }
+
+    let Some(content_length) = content_length else {
+        return Err("Missing header `Content-Length`.".to_owned());
+    };
+
+    let mut content = vec![0u8; content_length];
+    reader.read_exact(&mut content).map_err(|err| err.to_string())?;
+    serde_json::from_slice(&content)
+        .map(Some)
+        .map_err(|err| err.to_string())
+}
+
+pub fn write_message(writer: &mut impl Write, message: &Value) -> Result<(), String> {
+    let content = message.to_string();
+    write!(writer, "Content-Length: {}\r\n\r\n{}", content.len(), content).map_err(|err| err.to_string())?;
+    writer.flush().map_err(|err| err.to_string())
+}
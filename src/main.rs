@@ -1,3 +1,8 @@
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
 use meplang::*;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
@@ -5,129 +10,625 @@ use tracing_subscriber::util::SubscriberInitExt;
 const HELP_MESSAGE: &str = "\
 Usage: meplang <COMMAND>\n\n\
 Commands:\n\
-\tcompile: Compile a Meplang file into EVM bytecode.\n\
+\tbuild [TARGET]: Build the targets of the project described by `meplang.toml`, or only the target TARGET.\n\
+\tcompile: Compile contracts of a Meplang file into EVM bytecode.\n\
+\texplain <CODE>: Explain an error or warning code - ex: `meplang explain E0003`.\n\
+\tfmt <FILE>...: Format Meplang files.\n\
+\tlsp: Start the language server, over stdin and stdout.\n\
 \tversion: Print version information.\n\
+\t--standard-json: Read a Standard JSON input from stdin, and print the Standard JSON output.\n\n\
+Run `meplang <COMMAND> --help` for the options of a command.\n\
 ";
 
-fn main() {
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::fmt::layer())
-        .with(tracing_subscriber::filter::EnvFilter::from_default_env())
-        .init();
+const BUILD_HELP: &str = "\
+Usage: meplang build [TARGET] [OPTIONS]\n\n\
+Build the targets of the project described by `meplang.toml`, or only the target TARGET. The artifacts of each \
+contract are written as `<buildDir>/<TARGET>/<NAME>.json`.\n\n\
+Options:\n\
+\t--manifest <FILE>: Manifest of the project, `meplang.toml` by default.\n\
+\t--message-format=<human|json>: Print the errors and warnings as text (default) or as JSON.\n\
+\t--deny-warnings: Fail on warnings.\n\
+\t--watch: Build again when the manifest or a source changes.\n\
+\t-h, --help: Print this help.\n\
+";
+
+const COMPILE_HELP: &str = "\
+Usage: meplang compile -i <FILE> (-c <NAME>... | --all) [OPTIONS]\n\n\
+Compile contracts of a Meplang file into EVM bytecode. Without `-o` nor `--out-dir`, the bytecode of each contract \
+is printed.\n\n\
+Options:\n\
+\t-c, -contract <NAME>: Contract to compile, can be repeated.\n\
+\t--all: Compile all the contracts of the file.\n\
+\t-i, -input <FILE>: File to compile, `-` to read from stdin.\n\
+\t-o, -output <FILE>: File to write the artifacts of the contract to.\n\
+\t--out-dir <DIR>: Directory to write the artifacts of each contract to, as `<DIR>/<NAME>.json`.\n\
+\t--format <FORMAT>: Format of the artifacts, `meplang` (default) or `foundry` (written as `<DIR>/<FILE>.mep/<NAME>.json`).\n\
+\t--emit <KIND>: What to output, `artifacts` (default) or `asm` for an assembly listing (written as `<DIR>/<NAME>.asm`).\n\
+\t-s, -settings <JSON>: Compiler settings.\n\
+\t--settings-file <FILE>: File holding the compiler settings.\n\
+\t-D <NAME>=<0x..>: Value of a compile variable, can be repeated.\n\
+\t--message-format=<human|json>: Print the errors and warnings as text (default) or as JSON.\n\
+\t--deny-warnings: Fail on warnings.\n\
+\t--watch: Compile again when the input file changes.\n\
+\t-h, --help: Print this help.\n\
+";
+
+const EXPLAIN_HELP: &str = "\
+Usage: meplang explain <CODE>\n\n\
+Explain an error or warning code - ex: `meplang explain E0003`.\n\
+";
+
+const FMT_HELP: &str = "\
+Usage: meplang fmt [OPTIONS] <FILE>...\n\n\
+Format Meplang files in place.\n\n\
+Options:\n\
+\t--check: Only check that the files are formatted, and fail otherwise.\n\
+\t--max-width <N>: Maximum width of the lines, 120 by default.\n\
+\t-h, --help: Print this help.\n\
+";
+
+const LSP_HELP: &str = "\
+Usage: meplang lsp\n\n\
+Start the language server, over stdin and stdout.\n\
+";
 
+fn main() {
     let mut args = std::env::args();
     args.next();
 
-    let Some(mode) = args.next() else {
+    let mode = args.next();
+
+    // the language server uses stdout for the protocol
+    let log_to_stderr = mode.as_deref() == Some("lsp");
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::fmt::layer().with_writer(move || -> Box<dyn std::io::Write> {
+                if log_to_stderr {
+                    Box::new(std::io::stderr())
+                } else {
+                    Box::new(std::io::stdout())
+                }
+            }),
+        )
+        .with(
+            // the warnings of the compiler are shown by default
+            tracing_subscriber::filter::EnvFilter::builder()
+                .with_default_directive(tracing_subscriber::filter::LevelFilter::WARN.into())
+                .from_env_lossy(),
+        )
+        .init();
+
+    let Some(mode) = mode else {
         println!("Meplang - An EVM low-level language.\n\n{}", HELP_MESSAGE);
         return;
     };
 
     match mode.as_str() {
+        "-h" | "--help" | "help" => println!("Meplang - An EVM low-level language.\n\n{}", HELP_MESSAGE),
         "version" => println!("Meplang version: {}", env!("CARGO_PKG_VERSION")),
+        "--standard-json" => {
+            let mut input = String::new();
+            if let Err(err) = std::io::stdin().read_to_string(&mut input) {
+                tracing::error!("Could not read from stdin: {}", err);
+                std::process::exit(1);
+            }
+            println!("{}", compile_standard_json(&input));
+        },
+        "fmt" => {
+            let mut check = false;
+            let mut settings = FormatSettings::default();
+            let mut files = Vec::<String>::new();
+
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "-h" | "--help" => print_help(FMT_HELP),
+                    "--check" => check = true,
+                    "--max-width" => {
+                        let Some(max_width) = args.next().and_then(|next| next.parse::<usize>().ok()) else {
+                            tracing::error!("Expected a width after `{}`.", arg);
+                            std::process::exit(2);
+                        };
+                        settings.max_width = max_width;
+                    },
+                    _ => files.push(arg),
+                }
+            }
+
+            if files.is_empty() {
+                tracing::error!("Expected at least one file to format.");
+                std::process::exit(2);
+            }
+
+            let mut unformatted = false;
+            for file in files {
+                let input = match std::fs::read_to_string(&file) {
+                    Ok(input) => input,
+                    Err(err) => {
+                        tracing::error!("Could not open file `{}`: {}", file, err);
+                        std::process::exit(2);
+                    },
+                };
+                let formatted = match format_source(&input, &settings) {
+                    Ok(formatted) => formatted,
+                    Err(err) => {
+                        tracing::error!("Could not format the file `{}`:\n{}", file, err);
+                        std::process::exit(2);
+                    },
+                };
+                if formatted == input {
+                    continue;
+                }
+
+                if check {
+                    println!("The file `{}` is not formatted.", file);
+                    unformatted = true;
+                } else if let Err(err) = std::fs::write(&file, formatted) {
+                    tracing::error!("Could not write the file `{}`: {}", file, err);
+                    std::process::exit(2);
+                }
+            }
+
+            if unformatted {
+                std::process::exit(1);
+            }
+        },
+        "explain" => {
+            let Some(code) = args.next() else {
+                usage_error("Expected a code - ex: `meplang explain E0003`.");
+            };
+            if code == "-h" || code == "--help" {
+                print_help(EXPLAIN_HELP);
+            }
+            match Code::parse(&code) {
+                Some(code) => {
+                    println!("{}: {}\n\n{}", code.as_str(), code.title(), code.explanation());
+                    if let Some(name) = code.lint_name() {
+                        println!(
+                            "\nThis warning can be allowed on a contract or a block with `#[allow({})]`.",
+                            name
+                        );
+                    }
+                },
+                None => usage_error(&format!("Unknown code `{}`.", code)),
+            }
+        },
+        "lsp" => {
+            if args.any(|arg| arg == "-h" || arg == "--help") {
+                print_help(LSP_HELP);
+            }
+            if let Err(err) = run_lsp_server() {
+                tracing::error!("{}", err);
+                std::process::exit(1);
+            }
+        },
+        "build" => {
+            let mut target = Option::<String>::None;
+            let mut manifest = MANIFEST_FILE.to_owned();
+            let mut json_messages = false;
+            let mut deny_warnings = false;
+            let mut watch = false;
+
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "-h" | "--help" => print_help(BUILD_HELP),
+                    "--manifest" => manifest = expect_value(&mut args, &arg),
+                    "--message-format=human" => json_messages = false,
+                    "--message-format=json" => json_messages = true,
+                    "--deny-warnings" => deny_warnings = true,
+                    "--watch" => watch = true,
+                    _ if arg.starts_with('-') => usage_error(&format!("Unexpected argument `{}`.", arg)),
+                    _ => {
+                        if target.replace(arg).is_some() {
+                            usage_error("Target specified multiple times.");
+                        }
+                    },
+                }
+            }
+
+            let manifest = PathBuf::from(manifest);
+            if !watch {
+                if build(&manifest, target.as_deref(), json_messages, deny_warnings).is_none() {
+                    std::process::exit(1);
+                }
+                return;
+            }
+
+            let mut previous = BTreeMap::<String, ContractArtifacts>::new();
+            watch_files(
+                || project_files(&manifest),
+                || {
+                    if let Some(output) = build(&manifest, target.as_deref(), json_messages, deny_warnings) {
+                        let current = output
+                            .built
+                            .into_iter()
+                            .map(|built| {
+                                let name = format!("`{}` of the target `{}`", built.contract, built.target);
+                                (name, built.artifacts.main_artifacts().clone())
+                            })
+                            .collect::<BTreeMap<_, _>>();
+                        print_size_changes(&previous, &current);
+                        previous = current;
+                    }
+                },
+            );
+        },
         "compile" => {
-            let mut contract = Option::<String>::None;
+            let mut contracts = Vec::<String>::new();
+            let mut all = false;
             let mut input_file = Option::<String>::None;
             let mut output_file = Option::<String>::None;
+            let mut output_dir = Option::<String>::None;
+            let mut format = ArtifactFormat::default();
+            let mut emit_asm = false;
             let mut settings = Option::<CompilerSettings>::None;
+            let mut variables = Vec::<(String, Vec<u8>)>::new();
+            let mut json_messages = false;
+            let mut deny_warnings = false;
+            let mut watch = false;
 
             while let Some(arg) = args.next() {
                 match arg.as_str() {
-                    "-c" | "-contract" => {
-                        let Some(next) = args.next() else {
-                            tracing::error!("Expected an argument after `{}`.", arg);
-                            return;
-                        };
-                        if contract.replace(next).is_some() {
-                            tracing::error!("Contract name specified multiple times.");
-                            return;
-                        }
-                    },
+                    "-h" | "--help" => print_help(COMPILE_HELP),
+                    "-c" | "-contract" => contracts.push(expect_value(&mut args, &arg)),
+                    "--all" => all = true,
                     "-i" | "-input" => {
-                        let Some(next) = args.next() else {
-                            tracing::error!("Expected an argument after `{}`.", arg);
-                            return;
-                        };
-                        if input_file.replace(next).is_some() {
-                            tracing::error!("Input path specified multiple times.");
-                            return;
+                        if input_file.replace(expect_value(&mut args, &arg)).is_some() {
+                            usage_error("Input path specified multiple times.");
                         }
                     },
                     "-o" | "-output" => {
-                        let Some(next) = args.next() else {
-                            tracing::error!("Expected an argument after `{}`.", arg);
-                            return;
-                        };
-                        if output_file.replace(next).is_some() {
-                            tracing::error!("Output path specified multiple times.");
-                            return;
+                        if output_file.replace(expect_value(&mut args, &arg)).is_some() {
+                            usage_error("Output path specified multiple times.");
+                        }
+                    },
+                    "--format" => {
+                        format = match expect_value(&mut args, &arg).as_str() {
+                            "meplang" => ArtifactFormat::Meplang,
+                            "foundry" => ArtifactFormat::Foundry,
+                            other => {
+                                usage_error(&format!("Unknown format `{}`, expected `meplang` or `foundry`.", other))
+                            },
+                        }
+                    },
+                    "--emit" => {
+                        emit_asm = match expect_value(&mut args, &arg).as_str() {
+                            "artifacts" => false,
+                            "asm" => true,
+                            other => {
+                                usage_error(&format!("Unknown output `{}`, expected `artifacts` or `asm`.", other))
+                            },
                         }
                     },
-                    "-s" | "-settings" => {
-                        let Some(next) = args.next() else {
-                            tracing::error!("Expected an argument after `{}`.", arg);
-                            return;
+                    "--out-dir" => {
+                        if output_dir.replace(expect_value(&mut args, &arg)).is_some() {
+                            usage_error("Output directory specified multiple times.");
+                        }
+                    },
+                    "-s" | "-settings" | "--settings-file" => {
+                        let next = expect_value(&mut args, &arg);
+                        let json = if arg == "--settings-file" {
+                            match std::fs::read_to_string(&next) {
+                                Ok(json) => json,
+                                Err(err) => usage_error(&format!("Could not open file `{}`: {}", next, err)),
+                            }
+                        } else {
+                            next
                         };
-                        let decoded: CompilerSettings = match serde_json::from_str(&next) {
+                        let decoded: CompilerSettings = match serde_json::from_str(&json) {
                             Ok(decoded) => decoded,
-                            Err(err) => {
-                                tracing::error!("Unable to decode compiler settings: {}", err);
-                                return;
-                            },
+                            Err(err) => usage_error(&format!("Unable to decode compiler settings: {}", err)),
                         };
                         if settings.replace(decoded).is_some() {
-                            tracing::error!("Compiler settings specified multiple times.");
-                            return;
+                            usage_error("Compiler settings specified multiple times.");
                         }
                     },
-                    _ => {
-                        tracing::error!("Unexpected argument `{}`.", &arg);
-                        return;
+                    "--message-format=human" => json_messages = false,
+                    "--message-format=json" => json_messages = true,
+                    "--deny-warnings" => deny_warnings = true,
+                    "--watch" => watch = true,
+                    _ if arg.starts_with("-D") => {
+                        let definition = match arg.strip_prefix("-D").unwrap() {
+                            "" => expect_value(&mut args, &arg),
+                            definition => definition.to_owned(),
+                        };
+                        let Some((name, value)) = definition.split_once('=') else {
+                            usage_error(&format!(
+                                "Expected a compile variable as `NAME=0x..`, got `{}`.",
+                                definition
+                            ));
+                        };
+                        let value = match hex::decode(value.strip_prefix("0x").unwrap_or(value)) {
+                            Ok(value) => value,
+                            Err(err) => {
+                                usage_error(&format!("Invalid value of the compile variable `{}`: {}", name, err))
+                            },
+                        };
+                        variables.push((name.to_owned(), value));
                     },
+                    _ => usage_error(&format!("Unexpected argument `{}`.", arg)),
                 }
             }
 
-            let Some(contract) = contract else {
-                tracing::error!("Expected a contract name (-contract <CONTRACT_NAME>).");
-                return;
+            let Some(input_file) = input_file else {
+                usage_error("Expected an input file (-input <FILE>, or `-` to read from stdin).");
             };
+            if watch && input_file == "-" {
+                usage_error("Cannot watch the standard input.");
+            }
+            if !all && contracts.is_empty() {
+                usage_error("Expected a contract name (-contract <CONTRACT_NAME>), or `--all`.");
+            }
+            if output_file.is_some() && (all || contracts.len() > 1) {
+                usage_error("Many contracts are compiled, use `--out-dir <DIR>` to write their artifacts.");
+            }
 
-            let Some(input_file) = input_file else {
-                tracing::error!("Expected an input file (-input <CONTRACT_NAME>).");
-                return;
+            let mut settings = settings.unwrap_or_default();
+            for (name, value) in variables {
+                settings.add_variable(&name, value.into());
+            }
+
+            let options = CompileOptions {
+                contracts,
+                all,
+                input_file,
+                output_file,
+                output_dir,
+                format,
+                emit_asm,
+                settings,
+                json_messages,
+                deny_warnings,
             };
 
-            match compile_file(input_file.as_str(), contract.as_str(), settings.unwrap_or_default()) {
-                Ok(artifacts) => {
-                    if let Some(output_file) = output_file {
-                        match std::fs::write(&output_file, serde_json::to_string_pretty(&artifacts).unwrap()) {
-                            Ok(()) => {
-                                println!(
-                                    "Contract `{}` bytecode written in the file `{}`.",
-                                    contract, output_file
-                                );
-                                return;
-                            },
-                            Err(err) => {
-                                tracing::error!("{}", err);
-                                return;
-                            },
-                        }
-                    } else {
-                        println!(
-                            "Contract `{}` bytecode: {}",
-                            contract,
-                            format!("0x{}", hex::encode(artifacts.main_bytecode()))
-                        );
-                        return;
+            if !watch {
+                if compile(&options).is_none() {
+                    std::process::exit(1);
+                }
+                return;
+            }
+
+            let mut previous = BTreeMap::<String, ContractArtifacts>::new();
+            watch_files(
+                || vec![PathBuf::from(&options.input_file)],
+                || {
+                    if let Some(compiled) = compile(&options) {
+                        let current = compiled
+                            .into_iter()
+                            .map(|artifacts| (format!("`{}`", artifacts.main), artifacts.main_artifacts().clone()))
+                            .collect::<BTreeMap<_, _>>();
+                        print_size_changes(&previous, &current);
+                        previous = current;
                     }
                 },
-                Err(err) => {
-                    tracing::error!("{}", err);
-                    return;
-                },
+            );
+        },
+        _ => usage_error(&format!("Unexpected command `{}`.", mode)),
+    }
+}
+
+/// Prints the errors and warnings of a compilation, as JSON objects (one per line) or rendered with the source.
+fn print_report(report: &Report, json: bool) {
+    for diagnostic in report.diagnostics.iter() {
+        if json {
+            println!("{}", report.json(diagnostic));
+        } else if diagnostic.is_error() {
+            tracing::error!("{}", report.render(diagnostic));
+        } else {
+            tracing::warn!("{}", report.render(diagnostic));
+        }
+    }
+}
+
+/// Value following the argument `arg`.
+fn expect_value(args: &mut impl Iterator<Item = String>, arg: &str) -> String {
+    match args.next() {
+        Some(value) => value,
+        None => usage_error(&format!("Expected an argument after `{}`.", arg)),
+    }
+}
+
+/// Prints the help of a command, and exits.
+fn print_help(help: &str) -> ! {
+    print!("{}", help);
+    std::process::exit(0);
+}
+
+/// Reports a misuse of the command line, and exits with the status 2.
+fn usage_error(message: &str) -> ! {
+    tracing::error!("{}", message);
+    std::process::exit(2);
+}
+
+/// Options of the command `compile`.
+struct CompileOptions {
+    contracts: Vec<String>,
+    all: bool,
+    input_file: String,
+    output_file: Option<String>,
+    output_dir: Option<String>,
+    format: ArtifactFormat,
+    /// Outputs the assembly listing of the contracts instead of their artifacts.
+    emit_asm: bool,
+    settings: CompilerSettings,
+    json_messages: bool,
+    deny_warnings: bool,
+}
+
+/// Compiles the contracts, prints the diagnostics, and prints or writes the artifacts. Returns `None` when the
+/// compilation fails.
+fn compile(options: &CompileOptions) -> Option<Vec<Artifacts>> {
+    let (path, input) = if options.input_file == "-" {
+        let mut input = String::new();
+        if let Err(err) = std::io::stdin().read_to_string(&mut input) {
+            tracing::error!("Could not read from stdin: {}", err);
+            return None;
+        }
+        ("<stdin>".to_owned(), input)
+    } else {
+        match read_source(&options.input_file) {
+            Ok(input) => (options.input_file.clone(), input),
+            Err(report) => {
+                print_report(&report, options.json_messages);
+                return None;
+            },
+        }
+    };
+
+    let contracts = if options.all {
+        match enabled_contracts(&path, &input, &options.settings) {
+            Ok(declared) => declared,
+            Err(report) => {
+                print_report(&report, options.json_messages);
+                return None;
+            },
+        }
+    } else {
+        options.contracts.clone()
+    };
+    if contracts.is_empty() {
+        tracing::error!("No contract declared in `{}`.", path);
+        return None;
+    }
+
+    let (compiled, mut report) = compile_source_contracts(&path, &input, &contracts, &options.settings);
+    if options.deny_warnings {
+        report.deny_warnings();
+    }
+    print_report(&report, options.json_messages);
+    if report.has_errors() {
+        return None;
+    }
+
+    for artifacts in &compiled {
+        let output_file = match (&options.output_file, &options.output_dir) {
+            (Some(output_file), _) => Some(PathBuf::from(output_file)),
+            (None, Some(output_dir)) if options.emit_asm => {
+                Some(Path::new(output_dir).join(format!("{}.asm", artifacts.main)))
+            },
+            (None, Some(output_dir)) => Some(options.format.path(Path::new(output_dir), &path, &artifacts.main)),
+            (None, None) => None,
+        };
+
+        if let Some(output_file) = output_file {
+            if let Some(directory) = output_file
+                .parent()
+                .filter(|directory| !directory.as_os_str().is_empty())
+            {
+                if let Err(err) = std::fs::create_dir_all(directory) {
+                    tracing::error!("Could not create the directory `{}`: {}", directory.display(), err);
+                    return None;
+                }
+            }
+            let (content, output) = if options.emit_asm {
+                (assembly_listing(artifacts.main_artifacts()), "assembly listing")
+            } else {
+                (options.format.content(artifacts, options.settings.push0), "bytecode")
+            };
+            if let Err(err) = std::fs::write(&output_file, content) {
+                tracing::error!("Could not write the file `{}`: {}", output_file.display(), err);
+                return None;
             }
+            println!(
+                "Contract `{}` {} written in the file `{}`.",
+                artifacts.main,
+                output,
+                output_file.display()
+            );
+        } else if options.emit_asm {
+            println!(
+                "Contract `{}` assembly listing:\n{}",
+                artifacts.main,
+                assembly_listing(artifacts.main_artifacts())
+            );
+        } else {
+            println!(
+                "Contract `{}` bytecode: 0x{}",
+                artifacts.main,
+                hex::encode(artifacts.main_bytecode())
+            );
+        }
+    }
+
+    Some(compiled)
+}
+
+/// Builds a project, prints the diagnostics and the written artifacts. Returns `None` when the build fails.
+fn build(manifest: &Path, target: Option<&str>, json_messages: bool, deny_warnings: bool) -> Option<BuildOutput> {
+    let output = match build_project(manifest, target, deny_warnings) {
+        Ok(output) => output,
+        Err(err) => {
+            tracing::error!("{}", err);
+            return None;
         },
-        _ => tracing::error!("Unexpected command `{}`", mode),
+    };
+    for report in &output.reports {
+        print_report(report, json_messages);
+    }
+    if output.has_errors() {
+        return None;
+    }
+    for built in &output.built {
+        println!(
+            "Contract `{}` of the target `{}` written in the file `{}`.",
+            built.contract,
+            built.target,
+            built.path.display()
+        );
+    }
+    Some(output)
+}
+
+/// Runs `run`, then runs it again each time one of the files listed by `files` is created, modified or removed.
+/// Never returns: the process is stopped with Ctrl-C.
+fn watch_files(mut files: impl FnMut() -> Vec<PathBuf>, mut run: impl FnMut()) -> ! {
+    let snapshot = |files: Vec<PathBuf>| {
+        files
+            .into_iter()
+            .map(|file| {
+                let modified = std::fs::metadata(&file).and_then(|metadata| metadata.modified()).ok();
+                (file, modified)
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let mut last = snapshot(files());
+    run();
+    eprintln!("Watching for changes...");
+    loop {
+        std::thread::sleep(Duration::from_millis(200));
+        let current = snapshot(files());
+        if current != last {
+            last = current;
+            eprintln!();
+            run();
+            eprintln!("Watching for changes...");
+        }
+    }
+}
+
+/// Prints the changes of the sizes of the contracts and of their blocks since the previous build on the standard
+/// error, like the other messages of the watch mode.
+fn print_size_changes(previous: &BTreeMap<String, ContractArtifacts>, current: &BTreeMap<String, ContractArtifacts>) {
+    if previous.is_empty() {
+        return;
+    }
+
+    let mut changed = false;
+    for (name, artifacts) in current {
+        let changes = match previous.get(name) {
+            Some(previous) => artifacts.size_changes(previous),
+            None => vec![format!("new contract ({} bytes)", artifacts.bytecode.len())],
+        };
+        if changes.is_empty() {
+            continue;
+        }
+        changed = true;
+        eprintln!("Contract {}:", name);
+        for change in changes {
+            eprintln!("    {}", change);
+        }
+    }
+    if !changed {
+        eprintln!("No size change since the previous build.");
     }
 }
@@ -35,7 +35,7 @@ pub struct Located<T> {
     pub inner: T,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Location {
     pub start: usize,
     pub end: usize,
@@ -0,0 +1,49 @@
+use crate::abi::entry::{AbiEntry, AbiFunction};
+use crate::diagnostics::codes::Code;
+use crate::diagnostics::diagnostic::Diagnostic;
+use crate::parser::parser::Located;
+
+/// Merges the ABI entries declared with the attributes `function`, `event` and `error`, and the functions
+/// reached through the dispatcher. A declared function takes precedence over a dispatched function with the
+/// same selector, as it may describe its outputs and state mutability.
+pub fn build_abi(
+    declared: Vec<Located<AbiEntry>>,
+    dispatched: Vec<Located<AbiFunction>>,
+) -> Result<Vec<AbiEntry>, Diagnostic> {
+    let mut res = Vec::<AbiEntry>::with_capacity(declared.len() + dispatched.len());
+
+    for entry in declared {
+        if let Some(existing) = res.iter().find(|existing| same_identifier(existing, &entry)) {
+            return Err(Diagnostic::new(
+                Code::InvalidAbi,
+                format!(
+                    "The {} `{}` conflicts with the {} `{}` already declared.",
+                    entry.kind(),
+                    entry.signature(),
+                    existing.kind(),
+                    existing.signature()
+                ),
+            )
+            .at(&entry));
+        }
+        res.push(entry.inner);
+    }
+
+    for function in dispatched {
+        let entry = AbiEntry::Function(function.inner);
+        if !res.iter().any(|existing| same_identifier(existing, &entry)) {
+            res.push(entry);
+        }
+    }
+
+    Ok(res)
+}
+
+fn same_identifier(a: &AbiEntry, b: &AbiEntry) -> bool {
+    match (a, b) {
+        (AbiEntry::Function(a), AbiEntry::Function(b)) => a.selector() == b.selector(),
+        (AbiEntry::Error(a), AbiEntry::Error(b)) => a.selector() == b.selector(),
+        (AbiEntry::Event(a), AbiEntry::Event(b)) => a.topic() == b.topic(),
+        _ => false,
+    }
+}
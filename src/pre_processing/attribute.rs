@@ -1,13 +1,16 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use bytes::Bytes;
 use quick_impl::QuickImpl;
 
 use super::opcode::*;
 use super::pre_processing::get_compile_variable_value;
+use crate::abi::entry::{AbiEntry, AbiFunction};
+use crate::abi::signature::{parse_error, parse_event, parse_function};
 use crate::ast::{RAttribute, RAttributeArg, RAttributeEqualityRight};
-use crate::parser::error::new_error_from_located;
-use crate::parser::parser::{Located, Rule};
+use crate::diagnostics::codes::Code;
+use crate::diagnostics::diagnostic::Diagnostic;
+use crate::parser::parser::Located;
 use crate::types::bytes32::Bytes32;
 
 #[rustfmt::skip]
@@ -36,19 +39,41 @@ pub enum Attribute {
     #[quick_impl(pub is)]
     Last,
     Optimization(bool),
+    #[quick_impl(pub is)]
+    Dispatch,
+    #[quick_impl(pub is)]
+    Fallback,
+    Selector(AbiFunction),
+    Abi(AbiEntry),
+    #[quick_impl(pub is)]
+    Initcode,
+    Allow(Code),
+    /// Number of items a block takes from the stack and leaves on it.
+    Stack {
+        inputs: usize,
+        outputs: usize,
+    },
 }
 
 impl Attribute {
     pub fn is_contract_attribute(&self) -> bool {
-        !self.is_main() && !self.is_last() && !self.is_keep()
+        !self.is_block_marker() && !matches!(self, Self::Stack { .. })
     }
 
     pub fn is_block_attribute(&self) -> bool {
-        true
+        !self.is_initcode()
     }
 
     pub fn is_abstract_block_attribute(&self) -> bool {
-        !self.is_main() && !self.is_last() && !self.is_keep()
+        !self.is_block_marker() && !self.is_initcode() && !matches!(self, Self::Abi(_))
+    }
+
+    /// Attributes that mark a non-abstract block, and cannot be inherited by its content.
+    fn is_block_marker(&self) -> bool {
+        matches!(
+            self,
+            Self::Keep | Self::Main | Self::Last | Self::Dispatch | Self::Fallback | Self::Selector(_)
+        )
     }
 
     pub fn is_block_item_attribute(&self) -> bool {
@@ -60,116 +85,219 @@ impl Attribute {
     }
 
     pub fn from_r_attribute(
-        input: &str,
         r_attribute: &Located<RAttribute>,
         compile_variables: &HashMap<String, Bytes>,
-    ) -> Result<Self, pest::error::Error<Rule>> {
+    ) -> Result<Self, Diagnostic> {
         let name = r_attribute.name_str();
         match name {
             "assume" => {
                 let Some(arg) = &r_attribute.arg else {
-                    return Err(new_error_from_located(
-                        input,
-                        &r_attribute,
+                    return Err(Diagnostic::new(
+                        Code::InvalidAttribute,
                         "Argument required after `assume` attribute - ex: #[assume(msize = 0x20)]",
-                    ));
+                    )
+                    .at(r_attribute));
                 };
 
                 let RAttributeArg::AttributeEquality(eq) = &arg.inner else {
-                    return Err(new_error_from_located(
-                        input,
-                        &r_attribute,
+                    return Err(Diagnostic::new(
+                        Code::InvalidAttribute,
                         "Expected equality - ex: #[assume(msize = 0x20)]",
-                    ));
+                    )
+                    .at(r_attribute));
                 };
 
                 let bytes = match &eq.value.inner {
                     RAttributeEqualityRight::HexLiteral(hex_literal) => hex_literal.0.clone(),
                     RAttributeEqualityRight::CompileVariable(compile_variable) => {
-                        get_compile_variable_value(input, compile_variable, compile_variables)?.clone()
+                        get_compile_variable_value(compile_variable, compile_variables)?.clone()
                     },
-                    _ => return Err(new_error_from_located(
-                        input,
-                        &eq.value,
+                    _ => return Err(Diagnostic::new(
+                        Code::InvalidAttribute,
                         "Expected: \n hex literal - ex: #[assume(msize = 0x20)] \n or compile variable - ex: #[assume(chainid = $CHAINID$)]",
-                    )),
+                    )
+                    .at(&eq.value)),
                 };
 
                 if bytes.len() > 32 {
-                    return Err(new_error_from_located(
-                        input,
-                        &eq.value,
+                    return Err(Diagnostic::new(
+                        Code::InvalidAttribute,
                         "Hexadecimal literal must be less than 32 bytes",
-                    ));
+                    )
+                    .at(&eq.value));
                 }
 
                 let Some(op) = str_to_op(&eq.name_str().to_lowercase()) else {
-                    return Err(new_error_from_located(
-                        input,
-                        &eq.name,
-                        &format!("Unknown opcode `{}`", &eq.name_str()),
-                    ));
+                    return Err(
+                        Diagnostic::new(Code::UnknownOpcode, format!("Unknown opcode `{}`", &eq.name_str()))
+                            .at(&eq.name),
+                    );
                 };
 
                 if is_assumable_opcode(op) {
                     let Some(formatted) = Bytes32::from_bytes(&bytes, true) else {
-                        return Err(new_error_from_located(input, &eq.name, "Literal exceeds 32 bytes."));
+                        return Err(Diagnostic::new(Code::InvalidAttribute, "Literal exceeds 32 bytes.").at(&eq.name));
                     };
 
                     Ok(Self::Assume { op, v: formatted })
                 } else {
-                    Err(new_error_from_located(input, &eq.name, "Cannot assume this opcode"))
+                    Err(Diagnostic::new(Code::InvalidAttribute, "Cannot assume this opcode").at(&eq.name))
                 }
             },
             "clear_assume" => {
                 let Some(arg) = &r_attribute.arg else {
-                    return Err(new_error_from_located(
-                        input,
-                        &r_attribute,
+                    return Err(Diagnostic::new(
+                        Code::InvalidAttribute,
                         "Argument required after `clear_assume` attribute - ex: #[clear_assume(returndatasize)]",
-                    ));
+                    )
+                    .at(r_attribute));
                 };
 
                 let RAttributeArg::Variable(var) = &arg.inner else {
-                    return Err(new_error_from_located(
-                        input,
-                        &r_attribute,
+                    return Err(Diagnostic::new(
+                        Code::InvalidAttribute,
                         "Opcode name required after `clear_assume` attribute - ex: #[clear_assume(returndatasize)]",
-                    ));
+                    )
+                    .at(r_attribute));
                 };
 
                 let Some(op) = str_to_op(&var.as_str().to_lowercase()) else {
-                    return Err(new_error_from_located(
-                        input,
-                        &arg,
-                        &format!("Unknown opcode `{}`", var.as_str()),
-                    ));
+                    return Err(
+                        Diagnostic::new(Code::UnknownOpcode, format!("Unknown opcode `{}`", var.as_str())).at(arg),
+                    );
                 };
 
                 if is_assumable_opcode(op) {
                     Ok(Self::ClearAssume { op })
                 } else {
-                    Err(new_error_from_located(input, arg, "Cannot assume this opcode"))
+                    Err(Diagnostic::new(Code::InvalidAttribute, "Cannot assume this opcode").at(arg))
                 }
             },
             "enable_optimization" => Ok(Self::Optimization(true)),
             "disable_optimization" => Ok(Self::Optimization(false)),
+            "selector" => {
+                let (arg, signature) = signature_arg(r_attribute, "#[selector(\"transfer(address,uint256)\")]")?;
+                match parse_function(signature) {
+                    Ok(function) => Ok(Self::Selector(function)),
+                    Err(err) => {
+                        Err(Diagnostic::new(Code::InvalidAbi, format!("Invalid function signature: {}.", err)).at(arg))
+                    },
+                }
+            },
+            "function" => {
+                let (arg, signature) = signature_arg(
+                    r_attribute,
+                    "#[function(\"balanceOf(address owner) view returns (uint256)\")]",
+                )?;
+                match parse_function(signature) {
+                    Ok(function) => Ok(Self::Abi(AbiEntry::Function(function))),
+                    Err(err) => {
+                        Err(Diagnostic::new(Code::InvalidAbi, format!("Invalid function signature: {}.", err)).at(arg))
+                    },
+                }
+            },
+            "event" => {
+                let (arg, signature) = signature_arg(
+                    r_attribute,
+                    "#[event(\"Transfer(address indexed from, address indexed to, uint256 value)\")]",
+                )?;
+                match parse_event(signature) {
+                    Ok(event) => Ok(Self::Abi(AbiEntry::Event(event))),
+                    Err(err) => {
+                        Err(Diagnostic::new(Code::InvalidAbi, format!("Invalid event signature: {}.", err)).at(arg))
+                    },
+                }
+            },
+            "error" => {
+                let (arg, signature) = signature_arg(r_attribute, "#[error(\"Unauthorized(address caller)\")]")?;
+                match parse_error(signature) {
+                    Ok(error) => Ok(Self::Abi(AbiEntry::Error(error))),
+                    Err(err) => {
+                        Err(Diagnostic::new(Code::InvalidAbi, format!("Invalid error signature: {}.", err)).at(arg))
+                    },
+                }
+            },
+            "allow" => {
+                let Some(Located {
+                    location: _,
+                    inner: RAttributeArg::Variable(var),
+                }) = &r_attribute.arg
+                else {
+                    return Err(Diagnostic::new(
+                        Code::InvalidAttribute,
+                        "Warning name required after `allow` attribute - ex: #[allow(unused_block)]",
+                    )
+                    .at(r_attribute));
+                };
+
+                match Code::parse_lint(var.as_str()) {
+                    Some(code) => Ok(Self::Allow(code)),
+                    None => Err(
+                        Diagnostic::new(Code::InvalidAttribute, format!("Unknown warning `{}`", var.as_str()))
+                            .at(r_attribute.arg.as_ref().unwrap()),
+                    ),
+                }
+            },
+            "stack" => {
+                let Some(Located {
+                    location: _,
+                    inner: RAttributeArg::Stack(stack),
+                }) = &r_attribute.arg
+                else {
+                    return Err(Diagnostic::new(
+                        Code::InvalidAttribute,
+                        "Stack items required after `stack` attribute - ex: #[stack(a, b -> c)]",
+                    )
+                    .at(r_attribute));
+                };
+
+                Ok(Self::Stack {
+                    inputs: stack.inputs.len(),
+                    outputs: stack.outputs.len(),
+                })
+            },
+            "dispatch" => Ok(Self::Dispatch),
+            "fallback" => Ok(Self::Fallback),
+            "initcode" => Ok(Self::Initcode),
             "keep" => Ok(Self::Keep),
             "main" => Ok(Self::Main),
             "last" => Ok(Self::Last),
-            _ => Err(new_error_from_located(
-                input,
-                &r_attribute.name,
-                &format!("Unknown attribute `{}`", name),
-            )),
+            _ => Err(
+                Diagnostic::new(Code::InvalidAttribute, format!("Unknown attribute `{}`", name)).at(&r_attribute.name),
+            ),
         }
     }
 }
 
+fn signature_arg<'a>(
+    r_attribute: &'a Located<RAttribute>,
+    example: &str,
+) -> Result<(&'a Located<RAttributeArg>, &'a str), Diagnostic> {
+    match &r_attribute.arg {
+        Some(
+            arg @ Located {
+                location: _,
+                inner: RAttributeArg::StringLiteral(signature),
+            },
+        ) => Ok((arg, signature.0.as_str())),
+        _ => Err(Diagnostic::new(
+            Code::InvalidAttribute,
+            format!(
+                "Signature required after `{}` attribute - ex: {}",
+                r_attribute.name_str(),
+                example
+            ),
+        )
+        .at(r_attribute)),
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Attributes {
     pub assumes: HashMap<u8, Bytes32>,
     pub optimization: bool,
+    /// Warnings that are not reported.
+    pub allowed: HashSet<Code>,
 }
 
 impl Default for Attributes {
@@ -177,6 +305,7 @@ impl Default for Attributes {
         Self {
             assumes: HashMap::new(),
             optimization: true,
+            allowed: HashSet::new(),
         }
     }
 }
@@ -191,6 +320,9 @@ impl Attributes {
                 self.assumes.remove(&op);
             },
             Attribute::Optimization(enabled) => self.optimization = enabled,
+            Attribute::Allow(code) => {
+                self.allowed.insert(code);
+            },
             _ => (),
         }
     }
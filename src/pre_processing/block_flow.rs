@@ -1,19 +1,23 @@
 use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
 
 use bytes::{BufMut, Bytes, BytesMut};
 use indexmap::IndexSet;
 
 use super::attribute::Attribute;
 use super::opcode::str_to_op;
-use super::pre_processing::get_compile_variable_value;
+use super::pre_processing::{get_compile_variable_value, get_literal_value, get_negated_value, ItemSource};
 use crate::ast::*;
-use crate::parser::error::new_error_from_located;
-use crate::parser::parser::{Located, Location, Rule};
+use crate::diagnostics::codes::Code;
+use crate::diagnostics::diagnostic::{Diagnostic, Diagnostics};
+use crate::parser::parser::{Located, Location};
 use crate::types::bytes32::Bytes32;
 
 #[derive(Clone, Debug)]
 pub struct BlockFlow {
     pub items: Vec<BlockFlowItem>,
+    /// Sources of the bytes of each item.
+    pub sources: Vec<Vec<ItemSource>>,
     pub end_attributes: Vec<Attribute>,
     pub strong_deps: IndexSet<usize>,
     pub weak_deps: IndexSet<usize>,
@@ -21,7 +25,10 @@ pub struct BlockFlow {
 
 #[derive(Clone, Debug)]
 pub enum BlockFlowItem {
+    /// Opcodes.
     Bytes(Bytes),
+    /// Raw bytes - hex literals, constants and compile variables used outside of a `push`.
+    Data(Bytes),
     Contract(usize),
     BlockEsp(BlockFlowBlockRef),
     BlockStar(BlockFlowBlockRef),
@@ -46,133 +53,199 @@ pub enum BlockFlowPushInner {
     Constant(Bytes32),
     BlockPc(usize),
     BlockSize(usize),
+    ContractSize(usize),
+    ContractCodehash(usize),
+    Create2 {
+        deployer: [u8; 20],
+        salt: Bytes32,
+        index: usize,
+    },
 }
 
 pub fn analyze_block_flow(
-    input: &str,
     r_block_with_attr: &Located<WithAttributes<Located<RBlock>>>,
     constants: &HashMap<String, Bytes>,
     contract_names: &HashMap<String, usize>,
     block_names: &HashMap<String, usize>,
     contract_dependencies: &mut HashSet<usize>,
     compile_variables: &HashMap<String, Bytes>,
-) -> Result<BlockFlow, pest::error::Error<Rule>> {
+    errors: &mut Diagnostics,
+) -> BlockFlow {
     // tracing::info!("Analyzing flow block {}", r_block_with_attr.inner().name_str());
 
     let r_block = r_block_with_attr.inner();
 
     let mut items = Vec::<BlockFlowItem>::new();
+    let mut sources = Vec::<Vec<ItemSource>>::new();
     let mut current_bytes: Option<BytesMut> = None;
+    let mut current_data: Option<BytesMut> = None;
+    let mut current_bytes_sources = Vec::<ItemSource>::new();
+    let mut current_data_sources = Vec::<ItemSource>::new();
     let mut current_attributes = Vec::<Attribute>::new();
 
     let mut strong_deps = IndexSet::<usize>::new();
     let mut weak_deps = IndexSet::<usize>::new();
 
-    for r_item_with_attr in &r_block.items {
+    let mut analyze_item = |r_item_with_attr: &Located<WithAttributes<Located<RBlockItem>>>,
+                            constants: &HashMap<String, Bytes>|
+     -> Result<(), Diagnostic> {
+        if r_block.data {
+            check_data_item(r_item_with_attr)?;
+        }
+
         for r_attribute in &r_item_with_attr.attributes {
-            let attribute = Attribute::from_r_attribute(input, r_attribute, compile_variables)?;
+            let attribute = Attribute::from_r_attribute(r_attribute, compile_variables)?;
             if attribute.is_block_item_attribute() {
                 current_attributes.push(attribute);
             } else {
-                return Err(new_error_from_located(input, r_attribute, "Invalid line attribute."));
+                return Err(Diagnostic::new(Code::InvalidAttribute, "Invalid line attribute.").at(r_attribute));
             }
         }
 
         let r_item = r_item_with_attr.inner();
 
+        // the items of a repetition are already unrolled after it
+        if let RBlockItem::Repeat(_) = &r_item.inner {
+            return Ok(());
+        }
+
         if let RBlockItem::HexAlias(hex_alias) = &r_item.inner {
             match hex_alias {
-                RHexAlias::HexLiteral(hex_literal) => {
-                    append_or_create_bytes(&mut current_bytes, &hex_literal.0);
+                RHexAlias::Literal(_) | RHexAlias::Negation(_) => {
+                    let value = resolve_hex_alias(hex_alias, &r_item.location, constants, compile_variables)?;
+                    flush_bytes(
+                        &mut items,
+                        &mut sources,
+                        &mut current_bytes,
+                        &mut current_bytes_sources,
+                        BlockFlowItem::Bytes,
+                    );
+                    current_data_sources.push(next_source(&current_data, &r_item.location));
+                    append_or_create_bytes(&mut current_data, &value);
                 },
                 RHexAlias::Variable(variable) => {
                     let variable_name = variable.as_str();
                     if let Some(op) = str_to_op(variable_name) {
+                        flush_bytes(
+                            &mut items,
+                            &mut sources,
+                            &mut current_data,
+                            &mut current_data_sources,
+                            BlockFlowItem::Data,
+                        );
+                        current_bytes_sources.push(next_source(&current_bytes, &r_item.location));
                         push_or_create_bytes(&mut current_bytes, op);
                     } else if let Some(constant) = constants.get(variable_name) {
-                        append_or_create_bytes(&mut current_bytes, constant);
+                        flush_bytes(
+                            &mut items,
+                            &mut sources,
+                            &mut current_bytes,
+                            &mut current_bytes_sources,
+                            BlockFlowItem::Bytes,
+                        );
+                        current_data_sources.push(next_source(&current_data, &r_item.location));
+                        append_or_create_bytes(&mut current_data, constant);
                     } else {
-                        return Err(new_error_from_located(
-                            input,
-                            &r_item,
-                            &format!("Unknown opcode or constant`{}`.", variable_name),
-                        ));
+                        return Err(Diagnostic::new(
+                            Code::UnknownOpcode,
+                            format!("Unknown opcode or constant `{}`.", variable_name),
+                        )
+                        .at(r_item));
                     }
                 },
                 RHexAlias::CompileVariable(compile_variable) => {
-                    append_or_create_bytes(
+                    flush_bytes(
+                        &mut items,
+                        &mut sources,
                         &mut current_bytes,
-                        get_compile_variable_value(input, compile_variable, compile_variables)?,
+                        &mut current_bytes_sources,
+                        BlockFlowItem::Bytes,
+                    );
+                    current_data_sources.push(next_source(&current_data, &r_item.location));
+                    append_or_create_bytes(
+                        &mut current_data,
+                        get_compile_variable_value(compile_variable, compile_variables)?,
                     );
                 },
             }
-            continue;
+            return Ok(());
         }
 
-        if let Some(c_bytes) = current_bytes.take() {
-            items.push(BlockFlowItem::Bytes(c_bytes.into()));
-        }
+        flush_bytes(
+            &mut items,
+            &mut sources,
+            &mut current_bytes,
+            &mut current_bytes_sources,
+            BlockFlowItem::Bytes,
+        );
+        flush_bytes(
+            &mut items,
+            &mut sources,
+            &mut current_data,
+            &mut current_data_sources,
+            BlockFlowItem::Data,
+        );
 
         match &r_item.inner {
-            RBlockItem::HexAlias(_) => unreachable!(),
+            RBlockItem::HexAlias(_) | RBlockItem::Repeat(_) => unreachable!(),
             RBlockItem::BlockRef(RBlockRef::Star(RBlockRefStar::Variable(variable))) => {
                 let block_name = variable.as_str();
                 let Some(block_index) = block_names.get(block_name) else {
-                    return Err(new_error_from_located(
-                        input,
-                        r_item,
-                        &format!("Block `{}` not found in this contract.", block_name),
-                    ));
+                    return Err(Diagnostic::new(
+                        Code::UnknownName,
+                        format!("Block `{}` not found in this contract.", block_name),
+                    )
+                    .at(r_item));
                 };
 
                 strong_deps.insert(*block_index);
+                sources.push(Vec::new());
                 items.push(BlockFlowItem::BlockStar(BlockFlowBlockRef {
                     index: *block_index,
                     location: r_item.location.clone(),
-                    attributes: current_attributes,
+                    attributes: std::mem::take(&mut current_attributes),
                 }));
-                current_attributes = Vec::new();
             },
             RBlockItem::BlockRef(RBlockRef::Esp(block_ref_esp)) => match block_ref_esp {
                 RBlockRefEsp::Variable(variable) => {
                     let block_name = variable.as_str();
                     let Some(block_index) = block_names.get(block_name) else {
-                        return Err(new_error_from_located(
-                            input,
-                            r_item,
-                            &format!("Block `{}` not found in this contract.", block_name),
-                        ));
+                        return Err(Diagnostic::new(
+                            Code::UnknownName,
+                            format!("Block `{}` not found in this contract.", block_name),
+                        )
+                        .at(r_item));
                     };
 
                     strong_deps.insert(*block_index);
+                    sources.push(Vec::new());
                     items.push(BlockFlowItem::BlockEsp(BlockFlowBlockRef {
                         index: *block_index,
                         location: r_item.location.clone(),
-                        attributes: current_attributes,
+                        attributes: std::mem::take(&mut current_attributes),
                     }));
-                    current_attributes = Vec::new();
                 },
                 RBlockRefEsp::VariableWithField(variable_with_field) => {
                     let field_name = variable_with_field.field.as_str();
                     if field_name != "code" {
-                        return Err(new_error_from_located(
-                            input,
-                            &variable_with_field.field,
-                            &format!("Unknown field {}.", field_name),
-                        ));
+                        return Err(
+                            Diagnostic::new(Code::UnknownField, format!("Unknown field {}.", field_name))
+                                .at(&variable_with_field.field),
+                        );
                     }
 
                     let variable_name = variable_with_field.variable.as_str();
 
                     let Some(contract_index) = contract_names.get(variable_name) else {
-                        return Err(new_error_from_located(
-                            input,
-                            &variable_with_field.variable,
-                            &format!("Contract `{}` not found.", variable_name),
-                        ));
+                        return Err(Diagnostic::new(
+                            Code::UnknownName,
+                            format!("Contract `{}` not found.", variable_name),
+                        )
+                        .at(&variable_with_field.variable));
                     };
 
                     items.push(BlockFlowItem::Contract(*contract_index));
+                    sources.push(vec![next_source(&None, &r_item.location)]);
                     contract_dependencies.insert(*contract_index);
                 },
             },
@@ -183,158 +256,459 @@ pub fn analyze_block_flow(
                     "push" | "rpush" => true,
                     "lpush" => false,
                     _ => {
-                        return Err(new_error_from_located(
-                            input,
-                            &function.name,
-                            &format!("Unknown function `{}`.", function_name),
-                        ));
+                        return Err(Diagnostic::new(
+                            Code::InvalidPush,
+                            format!("Unknown function `{}`.", function_name),
+                        )
+                        .at(&function.name));
                     },
                 };
 
-                let push = match &function.arg.inner {
-                    RFunctionArg::HexAlias(RHexAlias::HexLiteral(hex_literal)) => {
-                        let Some(formatted) = Bytes32::from_bytes(&hex_literal.0, push_right) else {
-                            return Err(new_error_from_located(
-                                input,
-                                &function.arg,
-                                &format!("Push content exceeds 32 bytes."),
-                            ));
-                        };
-
-                        BlockFlowPushInner::Constant(formatted)
-                    },
-                    RFunctionArg::HexAlias(RHexAlias::Variable(variable)) => {
-                        let Some(constant_value) = constants.get(variable.as_str()) else {
-                            return Err(new_error_from_located(
-                                input,
-                                &function.arg,
-                                &format!("Unknown argument `{}`.", variable.as_str()),
-                            ));
-                        };
-
-                        let Some(formatted) = Bytes32::from_bytes(constant_value, push_right) else {
-                            return Err(new_error_from_located(
-                                input,
-                                &function.arg,
-                                &format!("Push content exceeds 32 bytes."),
-                            ));
-                        };
-
-                        BlockFlowPushInner::Constant(formatted)
-                    },
-                    RFunctionArg::HexAlias(RHexAlias::CompileVariable(compile_variable)) => {
-                        let bytes = get_compile_variable_value(input, compile_variable, compile_variables)?;
-                        let Some(formatted) = Bytes32::from_bytes(bytes, push_right) else {
-                            return Err(new_error_from_located(
-                                input,
-                                &function.arg,
-                                &format!("Push content exceeds 32 bytes."),
-                            ));
-                        };
-
-                        BlockFlowPushInner::Constant(formatted)
-                    },
-                    RFunctionArg::VariableWithField(variable_with_field) => {
-                        if !push_right {
-                            return Err(new_error_from_located(
-                                input,
-                                &variable_with_field.variable,
-                                &format!("Left push can only take constants as argument."),
-                            ));
-                        }
-
-                        let field_name = variable_with_field.field.as_str();
-                        let variable_name = variable_with_field.variable.as_str();
-                        match field_name {
-                            "pc" => {
-                                if let Some(block_index) = block_names.get(variable_name) {
-                                    weak_deps.insert(*block_index);
-                                    BlockFlowPushInner::BlockPc(*block_index)
-                                } else {
-                                    return Err(new_error_from_located(
-                                        input,
-                                        &variable_with_field.variable,
-                                        &format!("Block `{}` not found.", variable_name),
-                                    ));
+                let push =
+                    match &function.arg.inner {
+                        RFunctionArg::HexAlias(hex_alias @ (RHexAlias::Literal(_) | RHexAlias::Negation(_))) => {
+                            let bytes =
+                                resolve_hex_alias(hex_alias, &function.arg.location, constants, compile_variables)?;
+                            let Some(formatted) = Bytes32::from_bytes(&bytes, push_right) else {
+                                return Err(Diagnostic::new(Code::InvalidPush, "Push content exceeds 32 bytes.")
+                                    .at(&function.arg));
+                            };
+
+                            BlockFlowPushInner::Constant(formatted)
+                        },
+                        RFunctionArg::HexAlias(RHexAlias::Variable(variable)) => {
+                            let Some(constant_value) = constants.get(variable.as_str()) else {
+                                return Err(Diagnostic::new(
+                                    Code::UnknownName,
+                                    format!("Unknown argument `{}`.", variable.as_str()),
+                                )
+                                .at(&function.arg));
+                            };
+
+                            let Some(formatted) = Bytes32::from_bytes(constant_value, push_right) else {
+                                return Err(Diagnostic::new(Code::InvalidPush, "Push content exceeds 32 bytes.")
+                                    .at(&function.arg));
+                            };
+
+                            BlockFlowPushInner::Constant(formatted)
+                        },
+                        RFunctionArg::HexAlias(RHexAlias::CompileVariable(compile_variable)) => {
+                            let bytes = get_compile_variable_value(compile_variable, compile_variables)?;
+                            let Some(formatted) = Bytes32::from_bytes(bytes, push_right) else {
+                                return Err(Diagnostic::new(Code::InvalidPush, "Push content exceeds 32 bytes.")
+                                    .at(&function.arg));
+                            };
+
+                            BlockFlowPushInner::Constant(formatted)
+                        },
+                        RFunctionArg::VariableWithField(variable_with_field) => {
+                            if !push_right {
+                                return Err(Diagnostic::new(
+                                    Code::InvalidPush,
+                                    "Left push can only take constants as argument.",
+                                )
+                                .at(&variable_with_field.variable));
+                            }
+
+                            let field_name = variable_with_field.field.as_str();
+                            let variable_name = variable_with_field.variable.as_str();
+                            if let Some(block_index) = block_names.get(variable_name) {
+                                match field_name {
+                                    "pc" => {
+                                        weak_deps.insert(*block_index);
+                                        BlockFlowPushInner::BlockPc(*block_index)
+                                    },
+                                    "size" => {
+                                        weak_deps.insert(*block_index);
+                                        BlockFlowPushInner::BlockSize(*block_index)
+                                    },
+                                    _ => {
+                                        return Err(Diagnostic::new(
+                                            Code::UnknownField,
+                                            format!("Unknown block field `{}`.", field_name),
+                                        )
+                                        .at(&variable_with_field.field))
+                                    },
                                 }
-                            },
-                            "size" => {
-                                if let Some(block_index) = block_names.get(variable_name) {
-                                    weak_deps.insert(*block_index);
-                                    BlockFlowPushInner::BlockSize(*block_index)
-                                } else {
-                                    return Err(new_error_from_located(
-                                        input,
-                                        &variable_with_field.variable,
-                                        &format!("Block `{}` not found.", variable_name),
-                                    ));
+                            } else if let Some(contract_index) = contract_names.get(variable_name) {
+                                contract_dependencies.insert(*contract_index);
+                                match field_name {
+                                    "size" => BlockFlowPushInner::ContractSize(*contract_index),
+                                    "codehash" => BlockFlowPushInner::ContractCodehash(*contract_index),
+                                    _ => {
+                                        return Err(Diagnostic::new(
+                                            Code::UnknownField,
+                                            format!("Unknown contract field `{}`.", field_name),
+                                        )
+                                        .at(&variable_with_field.field))
+                                    },
                                 }
-                            },
-                            _ => {
-                                return Err(new_error_from_located(
-                                    input,
-                                    &variable_with_field.field,
-                                    &format!("Unknown field `{}`.", field_name),
-                                ))
-                            },
-                        }
-                    },
-                    RFunctionArg::VariablesConcat(concat) => {
-                        let mut bytes = BytesMut::new();
-                        for variable in &concat.0 {
-                            let value = match &variable.inner {
-                                RHexAlias::Variable(variable) => {
-                                    let Some(constant_value) = constants.get(variable.as_str()) else {
-                                        return Err(new_error_from_located(
-                                            input,
-                                            &function.arg,
-                                            &format!("Unknown argument `{}`.", variable.as_str()),
-                                        ));
-                                    };
-                                    constant_value
-                                },
-                                RHexAlias::HexLiteral(hex_literal) => &hex_literal.0,
-                                RHexAlias::CompileVariable(compile_variable) => {
-                                    get_compile_variable_value(input, compile_variable, compile_variables)?
-                                },
+                            } else {
+                                return Err(Diagnostic::new(
+                                    Code::UnknownName,
+                                    format!("Block or contract `{}` not found.", variable_name),
+                                )
+                                .at(&variable_with_field.variable));
+                            }
+                        },
+                        RFunctionArg::Create2(create2) => {
+                            if !push_right {
+                                return Err(Diagnostic::new(
+                                    Code::InvalidPush,
+                                    "Left push can only take constants as argument.",
+                                )
+                                .at(&function.arg));
+                            }
+
+                            let deployer = resolve_hex_alias(
+                                &create2.deployer,
+                                &create2.deployer.location,
+                                constants,
+                                compile_variables,
+                            )?;
+                            if deployer.len() > 20 {
+                                return Err(Diagnostic::new(
+                                    Code::InvalidPush,
+                                    "The deployer address exceeds 20 bytes.",
+                                )
+                                .at(&create2.deployer));
+                            }
+                            let mut deployer_address = [0u8; 20];
+                            deployer_address[20 - deployer.len()..].copy_from_slice(&deployer);
+
+                            let salt =
+                                resolve_hex_alias(&create2.salt, &create2.salt.location, constants, compile_variables)?;
+                            let Some(salt) = Bytes32::from_bytes(&salt, true) else {
+                                return Err(
+                                    Diagnostic::new(Code::InvalidPush, "The salt exceeds 32 bytes.").at(&create2.salt)
+                                );
                             };
 
-                            bytes.extend_from_slice(value);
-                        }
-
-                        let Some(formatted) = Bytes32::from_bytes(&bytes.into(), push_right) else {
-                            return Err(new_error_from_located(
-                                input,
-                                &function.arg,
-                                &format!("Push content exceeds 32 bytes."),
-                            ));
-                        };
+                            let contract_name = create2.contract.as_str();
+                            let Some(contract_index) = contract_names.get(contract_name) else {
+                                return Err(Diagnostic::new(
+                                    Code::UnknownName,
+                                    format!("Contract `{}` not found.", contract_name),
+                                )
+                                .at(&create2.contract));
+                            };
+                            contract_dependencies.insert(*contract_index);
+
+                            BlockFlowPushInner::Create2 {
+                                deployer: deployer_address,
+                                salt,
+                                index: *contract_index,
+                            }
+                        },
+                        RFunctionArg::Arithmetic(arithmetic) => {
+                            let value = resolve_arithmetic(arithmetic, constants, compile_variables)?;
+                            if push_right {
+                                BlockFlowPushInner::Constant(value)
+                            } else {
+                                let bytes = Bytes::copy_from_slice(value.right_content());
+                                BlockFlowPushInner::Constant(Bytes32::from_bytes(&bytes, false).unwrap())
+                            }
+                        },
+                        RFunctionArg::VariablesConcat(concat) => {
+                            let mut bytes = BytesMut::new();
+                            for variable in &concat.0 {
+                                let value = match &variable.inner {
+                                    RHexAlias::Variable(variable) => {
+                                        let Some(constant_value) = constants.get(variable.as_str()) else {
+                                            return Err(Diagnostic::new(
+                                                Code::UnknownName,
+                                                format!("Unknown argument `{}`.", variable.as_str()),
+                                            )
+                                            .at(&function.arg));
+                                        };
+                                        constant_value.clone()
+                                    },
+                                    RHexAlias::Literal(_) | RHexAlias::Negation(_) => {
+                                        resolve_hex_alias(variable, &variable.location, constants, compile_variables)?
+                                    },
+                                    RHexAlias::CompileVariable(compile_variable) => {
+                                        get_compile_variable_value(compile_variable, compile_variables)?.clone()
+                                    },
+                                };
+
+                                bytes.extend_from_slice(&value);
+                            }
+
+                            let Some(formatted) = Bytes32::from_bytes(&bytes.into(), push_right) else {
+                                return Err(Diagnostic::new(Code::InvalidPush, "Push content exceeds 32 bytes.")
+                                    .at(&function.arg));
+                            };
 
-                        BlockFlowPushInner::Constant(formatted)
-                    },
-                };
+                            BlockFlowPushInner::Constant(formatted)
+                        },
+                    };
 
                 items.push(BlockFlowItem::Push(BlockFlowPush {
                     inner: push,
-                    attributes: current_attributes,
+                    attributes: std::mem::take(&mut current_attributes),
                 }));
-                current_attributes = Vec::new();
+                sources.push(vec![next_source(&None, &r_item.location)]);
             },
         }
-    }
+        Ok(())
+    };
 
-    if let Some(c_bytes) = current_bytes.take() {
-        items.push(BlockFlowItem::Bytes(c_bytes.into()));
+    let mut unrolled = Vec::<UnrolledItem>::new();
+    let block_constants = Rc::new(constants.clone());
+    for r_item_with_attr in &r_block.items {
+        // the copies made before an error are still analyzed
+        let r_item_with_attr = std::slice::from_ref(r_item_with_attr);
+        if let Err(err) = unroll_items(r_item_with_attr, &block_constants, compile_variables, &mut unrolled) {
+            errors.push(err);
+            if unrolled.len() > MAX_UNROLLED_ITEMS {
+                break;
+            }
+        }
+    }
+    // an item repeated N times is reported once, not once per copy
+    let mut item_errors = Vec::<Diagnostic>::new();
+    for (r_item_with_attr, constants) in unrolled {
+        if let Err(err) = analyze_item(r_item_with_attr, &constants) {
+            let is_same = |other: &Diagnostic| {
+                other.code == err.code && other.location == err.location && other.message == err.message
+            };
+            if !item_errors.iter().any(is_same) {
+                item_errors.push(err);
+            }
+        }
+    }
+    for err in item_errors {
+        errors.push(err);
     }
 
-    Ok(BlockFlow {
+    flush_bytes(
+        &mut items,
+        &mut sources,
+        &mut current_bytes,
+        &mut current_bytes_sources,
+        BlockFlowItem::Bytes,
+    );
+    flush_bytes(
+        &mut items,
+        &mut sources,
+        &mut current_data,
+        &mut current_data_sources,
+        BlockFlowItem::Data,
+    );
+
+    BlockFlow {
         items,
+        sources,
         end_attributes: current_attributes,
         strong_deps,
         weak_deps,
+    }
+}
+
+/// Block item with the constants it sees.
+type UnrolledItem<'a> = (
+    &'a Located<WithAttributes<Located<RBlockItem>>>,
+    Rc<HashMap<String, Bytes>>,
+);
+
+/// Maximum number of repetitions of a `repeat`.
+const MAX_REPETITIONS: usize = 1024;
+/// Maximum number of items of a block once its repetitions are unrolled, as nested repetitions multiply.
+const MAX_UNROLLED_ITEMS: usize = 0x10000;
+
+/// Items of a block with the constants they see. A repetition is followed by the copies of its items, each seeing
+/// the index of its copy as a constant, and only carries its attributes.
+fn unroll_items<'a>(
+    r_items: &'a [Located<WithAttributes<Located<RBlockItem>>>],
+    constants: &Rc<HashMap<String, Bytes>>,
+    compile_variables: &HashMap<String, Bytes>,
+    unrolled: &mut Vec<UnrolledItem<'a>>,
+) -> Result<(), Diagnostic> {
+    for r_item_with_attr in r_items {
+        unrolled.push((r_item_with_attr, constants.clone()));
+
+        let RBlockItem::Repeat(repeat) = &r_item_with_attr.inner().inner else {
+            continue;
+        };
+        let count = repeat_count(repeat, constants, compile_variables)?;
+
+        for index in 0..count {
+            let constants = match &repeat.index {
+                Some(name) => {
+                    let mut constants = constants.as_ref().clone();
+                    let value = Bytes32::from_usize(index).right_content().to_vec();
+                    let value = if value.is_empty() { vec![0] } else { value };
+                    constants.insert(name.as_str().to_owned(), value.into());
+                    Rc::new(constants)
+                },
+                None => constants.clone(),
+            };
+            unroll_items(&repeat.items, &constants, compile_variables, unrolled)?;
+            if unrolled.len() > MAX_UNROLLED_ITEMS {
+                return Err(Diagnostic::new(
+                    Code::InvalidRepeat,
+                    format!(
+                        "A block cannot hold more than {} items once its repetitions are unrolled.",
+                        MAX_UNROLLED_ITEMS
+                    ),
+                )
+                .at(&repeat.count));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Number of repetitions of a `repeat`, whose index must not hide a name.
+fn repeat_count(
+    repeat: &RRepeat,
+    constants: &HashMap<String, Bytes>,
+    compile_variables: &HashMap<String, Bytes>,
+) -> Result<usize, Diagnostic> {
+    if let Some(name) = &repeat.index {
+        if constants.contains_key(name.as_str()) {
+            return Err(Diagnostic::new(
+                Code::InvalidRepeat,
+                format!(
+                    "The index `{}` is already the name of a constant or of the index of an enclosing repeat.",
+                    name.as_str()
+                ),
+            )
+            .at(name));
+        }
+        if str_to_op(name.as_str()).is_some() || is_function_name(name.as_str()) {
+            return Err(Diagnostic::new(
+                Code::InvalidRepeat,
+                format!("The index `{}` is the name of an opcode or a function.", name.as_str()),
+            )
+            .at(name));
+        }
+    }
+
+    let count = resolve_hex_alias(&repeat.count, &repeat.count.location, constants, compile_variables)?;
+    let count = Bytes32::from_bytes(&count, true)
+        .map(|count| count.right_content().to_vec())
+        .filter(|count| count.len() <= 2)
+        .map(|count| count.iter().fold(0, |acc, byte| acc << 8 | *byte as usize))
+        .filter(|count| *count <= MAX_REPETITIONS);
+    count.ok_or_else(|| {
+        Diagnostic::new(
+            Code::InvalidRepeat,
+            format!("A block item cannot be repeated more than {} times.", MAX_REPETITIONS),
+        )
+        .at(&repeat.count)
     })
 }
 
+/// Value of an arithmetic expression, modulo 2^256.
+fn resolve_arithmetic(
+    arithmetic: &RArithmetic,
+    constants: &HashMap<String, Bytes>,
+    compile_variables: &HashMap<String, Bytes>,
+) -> Result<Bytes32, Diagnostic> {
+    let operand = |hex_alias: &Located<RHexAlias>| -> Result<Bytes32, Diagnostic> {
+        let value = resolve_hex_alias(hex_alias, &hex_alias.location, constants, compile_variables)?;
+        Bytes32::from_bytes(&value, true)
+            .ok_or_else(|| Diagnostic::new(Code::InvalidPush, "Operand exceeds 32 bytes.").at(hex_alias))
+    };
+
+    // the terms of the sum, each with its sign, once their products are computed
+    let mut terms = vec![(RArithmeticOperator::Add, operand(&arithmetic.first)?)];
+    for (operator, hex_alias) in &arithmetic.rest {
+        let value = operand(hex_alias)?;
+        match operator {
+            RArithmeticOperator::Mul => {
+                let last = terms.last_mut().unwrap();
+                last.1 = &last.1 * &value;
+            },
+            _ => terms.push((*operator, value)),
+        }
+    }
+
+    Ok(terms
+        .iter()
+        .fold(Bytes32::default(), |acc, (operator, value)| match operator {
+            RArithmeticOperator::Sub => &acc - value,
+            _ => &acc + value,
+        }))
+}
+
+pub fn resolve_hex_alias(
+    hex_alias: &RHexAlias,
+    location: &Location,
+    constants: &HashMap<String, Bytes>,
+    compile_variables: &HashMap<String, Bytes>,
+) -> Result<Bytes, Diagnostic> {
+    match hex_alias {
+        RHexAlias::Negation(negation) => {
+            let value = resolve_hex_alias(&negation.0, &negation.0.location, constants, compile_variables)?;
+            get_negated_value(&value, location)
+        },
+        RHexAlias::Literal(literal) => get_literal_value(literal, location),
+        RHexAlias::Variable(variable) => match constants.get(variable.as_str()) {
+            Some(constant_value) => Ok(constant_value.clone()),
+            None => Err(
+                Diagnostic::new(Code::UnknownName, format!("Unknown constant `{}`.", variable.as_str()))
+                    .at_location(location),
+            ),
+        },
+        RHexAlias::CompileVariable(compile_variable) => {
+            Ok(get_compile_variable_value(compile_variable, compile_variables)?.clone())
+        },
+    }
+}
+
+/// A data block only holds raw bytes: literals, constants, compile variables, and the code of other contracts.
+fn check_data_item(r_item_with_attr: &Located<WithAttributes<Located<RBlockItem>>>) -> Result<(), Diagnostic> {
+    if let Some(r_attribute) = r_item_with_attr.attributes.first() {
+        return Err(Diagnostic::new(
+            Code::InvalidDataBlock,
+            "The items of a data block cannot have attributes.",
+        )
+        .at(r_attribute));
+    }
+
+    let r_item = r_item_with_attr.inner();
+    let is_data = match &r_item.inner {
+        RBlockItem::HexAlias(RHexAlias::Variable(variable)) => str_to_op(variable.as_str()).is_none(),
+        RBlockItem::HexAlias(_) => true,
+        RBlockItem::BlockRef(RBlockRef::Esp(RBlockRefEsp::VariableWithField(_))) => true,
+        RBlockItem::BlockRef(_) | RBlockItem::Function(_) => false,
+        // its items are checked one by one
+        RBlockItem::Repeat(_) => true,
+    };
+    if is_data {
+        Ok(())
+    } else {
+        Err(Diagnostic::new(
+            Code::InvalidDataBlock,
+            "A data block can only contain raw bytes, literals and the code of contracts.",
+        )
+        .at(r_item))
+    }
+}
+
+fn flush_bytes(
+    items: &mut Vec<BlockFlowItem>,
+    sources: &mut Vec<Vec<ItemSource>>,
+    current_bytes: &mut Option<BytesMut>,
+    current_sources: &mut Vec<ItemSource>,
+    item: fn(Bytes) -> BlockFlowItem,
+) {
+    if let Some(c_bytes) = current_bytes.take() {
+        items.push(item(c_bytes.into()));
+        sources.push(std::mem::take(current_sources));
+    }
+}
+
+/// Source of the next bytes appended to `current_bytes`.
+fn next_source(current_bytes: &Option<BytesMut>, location: &Location) -> ItemSource {
+    ItemSource {
+        offset: current_bytes.as_ref().map_or(0, |c_bytes| c_bytes.len()),
+        location: location.clone(),
+    }
+}
+
 pub fn append_or_create_bytes(current_bytes: &mut Option<BytesMut>, new_bytes: &Bytes) {
     if let Some(c_bytes) = current_bytes.as_mut() {
         c_bytes.extend_from_slice(new_bytes);
@@ -359,3 +733,36 @@ pub fn is_function_name(name: &str) -> bool {
         _ => false,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::compile::file::compile_source;
+    use crate::compile::settings::CompilerSettings;
+    use crate::diagnostics::codes::Code;
+
+    #[test]
+    pub fn test_arithmetic_precedence() {
+        let input = "contract A { block main { push(0x0a - 0x02 * 0x03 + 0x01) push(0x02 * 0x03 * 0x04 - 0x0100) } }";
+        let (artifacts, _) = compile_source("a.mep", input, "A", CompilerSettings::default()).unwrap();
+        // 0x05, then 0x18 - 0x0100 pushed as the bitwise not of 0xe7
+        assert_eq!(hex::encode(&artifacts.contracts["A"].bytecode), "600560e719");
+    }
+
+    #[test]
+    pub fn test_nested_repeat_limit() {
+        let input = "\
+contract A {
+    block main {
+        repeat(0x0400) { repeat(0x0400) { repeat(0x0400) { caller } } }
+        repeat(0x0400) { caller }
+    }
+}";
+        let report = compile_source("a.mep", input, "A", CompilerSettings::default()).unwrap_err();
+        let codes = report
+            .diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.code)
+            .collect::<Vec<_>>();
+        assert_eq!(codes, [Code::InvalidRepeat]);
+    }
+}
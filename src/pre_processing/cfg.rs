@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+
+use bytes::Bytes;
+
+use super::pre_processing::get_literal_value;
+use crate::ast::{RAttribute, RAttributeArg, RBlockItem, RFile, WithAttributes};
+use crate::diagnostics::codes::Code;
+use crate::diagnostics::diagnostic::{Diagnostic, Diagnostics};
+use crate::parser::parser::Located;
+
+const CFG_EXAMPLE: &str = "ex: #[cfg(TESTNET)], #[cfg(CHAIN == 0x01)] or #[cfg(not(TESTNET))]";
+
+fn is_cfg(r_attribute: &Located<RAttribute>) -> bool {
+    r_attribute.name_str() == "cfg"
+}
+
+/// Whether the `cfg` attributes among `attributes` all hold for the compile variables.
+pub fn is_enabled(
+    attributes: &[Located<RAttribute>],
+    compile_variables: &HashMap<String, Bytes>,
+) -> Result<bool, Diagnostic> {
+    let mut res = true;
+    for r_attribute in attributes.iter().filter(|r_attribute| is_cfg(r_attribute)) {
+        let Some(arg) = &r_attribute.arg else {
+            return Err(Diagnostic::new(
+                Code::InvalidAttribute,
+                format!("Condition required after `cfg` attribute - {}", CFG_EXAMPLE),
+            )
+            .at(r_attribute));
+        };
+        res &= evaluate(arg, compile_variables)?;
+    }
+    Ok(res)
+}
+
+/// A variable holds when it is defined. A comparison holds when the variable is defined and has the same value as
+/// the literal, the leading zero bytes apart.
+fn evaluate(arg: &Located<RAttributeArg>, compile_variables: &HashMap<String, Bytes>) -> Result<bool, Diagnostic> {
+    match &arg.inner {
+        RAttributeArg::Variable(var) => Ok(compile_variables.contains_key(var.as_str())),
+        RAttributeArg::Comparison(comparison) => {
+            let expected = get_literal_value(&comparison.value, &comparison.value.location)?;
+            Ok(compile_variables
+                .get(comparison.name_str())
+                .is_some_and(|value| without_leading_zeros(value) == without_leading_zeros(&expected)))
+        },
+        RAttributeArg::Not(arg) => Ok(!evaluate(arg, compile_variables)?),
+        _ => Err(Diagnostic::new(
+            Code::InvalidAttribute,
+            format!("Expected a condition - {}", CFG_EXAMPLE),
+        )
+        .at(arg)),
+    }
+}
+
+fn without_leading_zeros(value: &[u8]) -> &[u8] {
+    let start = value.iter().position(|byte| *byte != 0).unwrap_or(value.len());
+    &value[start..]
+}
+
+/// Removes the contracts, blocks, constants and block items disabled by their `cfg` attributes, and the `cfg`
+/// attributes of the others, so that the rest of the pre-processing never sees them.
+pub fn apply_cfg(r_file: &mut RFile, compile_variables: &HashMap<String, Bytes>) -> Result<(), Diagnostics> {
+    let mut errors = Diagnostics::default();
+
+    retain_enabled(&mut r_file.0, compile_variables, &mut errors);
+    for r_contract in &mut r_file.0 {
+        let r_contract = &mut r_contract.inner.inner.inner;
+        retain_enabled(&mut r_contract.constants, compile_variables, &mut errors);
+        retain_enabled(&mut r_contract.blocks, compile_variables, &mut errors);
+        for r_block in &mut r_contract.blocks {
+            retain_enabled_items(&mut r_block.inner.inner.inner.items, compile_variables, &mut errors);
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Block items, and the items of the repetitions among them.
+fn retain_enabled_items(
+    items: &mut Vec<Located<WithAttributes<Located<RBlockItem>>>>,
+    compile_variables: &HashMap<String, Bytes>,
+    errors: &mut Diagnostics,
+) {
+    retain_enabled(items, compile_variables, errors);
+    for r_item in items {
+        if let RBlockItem::Repeat(r_repeat) = &mut r_item.inner.inner.inner {
+            retain_enabled_items(&mut r_repeat.items, compile_variables, errors);
+        }
+    }
+}
+
+/// An item whose condition is invalid is kept, to report the errors of the rest of the file.
+fn retain_enabled<T>(
+    items: &mut Vec<Located<WithAttributes<T>>>,
+    compile_variables: &HashMap<String, Bytes>,
+    errors: &mut Diagnostics,
+) {
+    items.retain_mut(|item| {
+        let enabled = match is_enabled(&item.attributes, compile_variables) {
+            Ok(enabled) => enabled,
+            Err(err) => {
+                errors.push(err);
+                true
+            },
+        };
+        item.inner.attributes.retain(|r_attribute| !is_cfg(r_attribute));
+        enabled
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile::file::compile_source;
+    use crate::compile::settings::CompilerSettings;
+
+    #[test]
+    pub fn test_cfg_in_repeat() {
+        let input = "\
+contract A {
+    block main {
+        repeat(0x02) {
+            #[cfg(DEBUG)]
+            push(0x01)
+            repeat(0x02) { #[cfg(not(DEBUG))] push(0x02) }
+        }
+        stop
+    }
+}";
+        let compile = |settings: CompilerSettings| {
+            let (artifacts, _) = compile_source("a.mep", input, "A", settings).unwrap();
+            hex::encode(&artifacts.contracts["A"].bytecode)
+        };
+
+        assert_eq!(compile(CompilerSettings::default()), "600260026002600200");
+        let mut settings = CompilerSettings::default();
+        settings
+            .variables
+            .insert("DEBUG".to_owned(), Bytes::from_static(&[0x01]));
+        assert_eq!(compile(settings), "6001600100");
+    }
+}
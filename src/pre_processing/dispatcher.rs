@@ -0,0 +1,370 @@
+use std::collections::HashMap;
+
+use bytes::Bytes;
+
+use crate::ast::*;
+use crate::compile::settings::DispatchStrategy;
+use crate::diagnostics::codes::Code;
+use crate::diagnostics::diagnostic::Diagnostic;
+use crate::parser::parser::{Located, Location};
+
+/// Maximum number of selectors compared one after the other by the binary search dispatcher.
+const BINARY_SEARCH_LEAF_SIZE: usize = 4;
+
+#[derive(Clone, Debug)]
+pub struct DispatchEntry {
+    pub selector: [u8; 4],
+    pub block_index: usize,
+    pub location: Location,
+}
+
+/// Appends the selector dispatcher at the end of the block `dispatch_index`. The blocks needed by the
+/// dispatcher are added after the existing blocks, and their names are registered in `block_names`.
+///
+/// The selector is left on the stack when jumping to the selected block (or to the fallback).
+pub fn expand_dispatcher(
+    r_blocks: &[RBlockWithAttr],
+    dispatch_index: usize,
+    fallback_index: Option<usize>,
+    mut entries: Vec<DispatchEntry>,
+    strategy: DispatchStrategy,
+    block_names: &mut HashMap<String, usize>,
+) -> Result<Vec<RBlockWithAttr>, Diagnostic> {
+    let mut sorted = entries.clone();
+    sorted.sort_by_key(|entry| (entry.selector, entry.location.start));
+    for pair in sorted.windows(2) {
+        if pair[0].selector == pair[1].selector {
+            return Err(Diagnostic::new(
+                Code::InvalidDispatch,
+                format!(
+                    "Selector 0x{} already used by the block `{}`.",
+                    hex::encode(pair[1].selector),
+                    r_blocks[pair[0].block_index].name_str()
+                ),
+            )
+            .at_location(&pair[1].location)
+            .with_label(&pair[0].location, "first used here"));
+        }
+    }
+
+    for target in entries.iter().map(|entry| entry.block_index).chain(fallback_index) {
+        let r_block = r_blocks[target].inner();
+        let starts_with_jumpdest = match r_block.items.first().map(|item| &item.inner().inner) {
+            Some(RBlockItem::HexAlias(RHexAlias::Variable(variable))) => {
+                variable.as_str().eq_ignore_ascii_case("jumpdest")
+            },
+            _ => false,
+        };
+        if !starts_with_jumpdest {
+            return Err(Diagnostic::new(
+                Code::InvalidDispatch,
+                "A block reached through the dispatcher must start with `jumpdest`.",
+            )
+            .at_location(&r_block.name.location));
+        }
+    }
+
+    let mut builder = DispatcherBuilder {
+        r_blocks,
+        dispatch_name: r_blocks[dispatch_index].name_str().to_owned(),
+        location: r_blocks[dispatch_index].name.location.clone(),
+        fallback: fallback_index.map(|index| r_blocks[index].name_str().to_owned()),
+        blocks: Vec::new(),
+        names_count: 0,
+    };
+
+    let mut items = builder.load_selector();
+    match strategy {
+        DispatchStrategy::Linear => builder.linear(&mut items, &entries),
+        DispatchStrategy::BinarySearch => {
+            entries.sort_by_key(|entry| entry.selector);
+            builder.binary_search(&mut items, &entries);
+        },
+        DispatchStrategy::JumpTable => builder.jump_table(&mut items, &entries),
+    }
+
+    let mut res = r_blocks.to_vec();
+    res[dispatch_index].inner.inner.items.append(&mut items);
+    for (name, items) in builder.blocks {
+        block_names.insert(name.clone(), res.len());
+        res.push(builder_block(&builder.location, name, items));
+    }
+
+    Ok(res)
+}
+
+type RBlockWithAttr = Located<WithAttributes<Located<RBlock>>>;
+type RItem = Located<WithAttributes<Located<RBlockItem>>>;
+
+struct DispatcherBuilder<'a> {
+    r_blocks: &'a [RBlockWithAttr],
+    dispatch_name: String,
+    location: Location,
+    fallback: Option<String>,
+    blocks: Vec<(String, Vec<RItem>)>,
+    names_count: usize,
+}
+
+impl DispatcherBuilder<'_> {
+    /// Pushes the 4 bytes selector of the calldata on the stack.
+    fn load_selector(&self) -> Vec<RItem> {
+        vec![
+            self.push(Bytes::new()),
+            self.op("calldataload"),
+            self.push(vec![0xe0].into()),
+            self.op("shr"),
+        ]
+    }
+
+    fn linear(&mut self, items: &mut Vec<RItem>, entries: &[DispatchEntry]) {
+        for entry in entries {
+            self.compare_and_jump(items, entry);
+        }
+        self.fallback(items);
+    }
+
+    fn binary_search(&mut self, items: &mut Vec<RItem>, entries: &[DispatchEntry]) {
+        if entries.len() <= BINARY_SEARCH_LEAF_SIZE {
+            self.linear(items, entries);
+            return;
+        }
+
+        let mid = entries.len() / 2;
+        let lower_name = self.new_block_name("lower");
+
+        // jumps to the lower half if `selector < pivot`
+        items.push(self.op("dup1"));
+        items.push(self.push(entries[mid].selector.to_vec().into()));
+        items.push(self.op("gt"));
+        items.push(self.push_pc(&lower_name));
+        items.push(self.op("jumpi"));
+        self.binary_search(items, &entries[mid..]);
+
+        let mut lower_items = vec![self.op("jumpdest")];
+        self.binary_search(&mut lower_items, &entries[..mid]);
+        self.blocks.push((lower_name, lower_items));
+    }
+
+    /// The table holds one `push(bucket.pc)` per bucket, and the argument of the `n`-th push is copied in
+    /// memory at `0x1e..0x20` before jumping to it.
+    fn jump_table(&mut self, items: &mut Vec<RItem>, entries: &[DispatchEntry]) {
+        let buckets_count = entries.len().next_power_of_two();
+        let mut buckets = vec![Vec::<DispatchEntry>::new(); buckets_count];
+        for entry in entries {
+            buckets[u32::from_be_bytes(entry.selector) as usize % buckets_count].push(entry.clone());
+        }
+
+        let table_name = self.new_block_name("table");
+
+        items.push(self.push(vec![0x02].into()));
+        items.push(self.op("dup2"));
+        items.push(self.push(trim_leading_zeros(&((buckets_count - 1) as u32).to_be_bytes())));
+        items.push(self.op("and"));
+        items.push(self.push(vec![0x03].into()));
+        items.push(self.op("mul"));
+        items.push(self.push_pc(&table_name));
+        items.push(self.op("add"));
+        items.push(self.push(vec![0x01].into()));
+        items.push(self.op("add"));
+        items.push(self.push(vec![0x1e].into()));
+        items.push(self.op("codecopy"));
+        items.push(self.push(Bytes::new()));
+        items.push(self.op("mload"));
+        items.push(self.push(vec![0xff, 0xff].into()));
+        items.push(self.op("and"));
+        items.push(self.op("jump"));
+
+        let mut default_name: Option<String> = self.fallback.clone();
+        let mut table_items = Vec::<RItem>::with_capacity(buckets_count);
+        for bucket in buckets {
+            if bucket.is_empty() {
+                let name = match &default_name {
+                    Some(name) => name.clone(),
+                    None => {
+                        let name = self.new_block_name("revert");
+                        let mut revert_items = vec![self.op("jumpdest")];
+                        self.fallback(&mut revert_items);
+                        self.blocks.push((name.clone(), revert_items));
+                        default_name.replace(name.clone());
+                        name
+                    },
+                };
+                table_items.push(self.push_pc(&name));
+            } else {
+                let name = self.new_block_name("bucket");
+                let mut bucket_items = vec![self.op("jumpdest")];
+                self.linear(&mut bucket_items, &bucket);
+                self.blocks.push((name.clone(), bucket_items));
+                table_items.push(self.push_pc(&name));
+            }
+        }
+        self.blocks.push((table_name, table_items));
+    }
+
+    fn compare_and_jump(&self, items: &mut Vec<RItem>, entry: &DispatchEntry) {
+        items.push(self.op("dup1"));
+        items.push(self.push(entry.selector.to_vec().into()));
+        items.push(self.op("eq"));
+        items.push(self.push_pc(self.r_blocks[entry.block_index].name_str()));
+        items.push(self.op("jumpi"));
+    }
+
+    fn fallback(&self, items: &mut Vec<RItem>) {
+        if let Some(fallback) = &self.fallback {
+            items.push(self.push_pc(fallback));
+            items.push(self.op("jump"));
+        } else {
+            items.push(self.push(Bytes::new()));
+            items.push(self.push(Bytes::new()));
+            items.push(self.op("revert"));
+        }
+    }
+
+    /// The generated names contain a dot, so that they cannot conflict with a name declared in the file.
+    fn new_block_name(&mut self, kind: &str) -> String {
+        self.names_count += 1;
+        format!("{}.{}_{}", self.dispatch_name, kind, self.names_count - 1)
+    }
+
+    fn op(&self, name: &str) -> RItem {
+        self.item(RHexAlias::Variable(RVariable(name.to_owned())).into())
+    }
+
+    fn push(&self, bytes: Bytes) -> RItem {
+        self.function(RFunctionArg::HexAlias(RHexAlias::Literal(RLiteral::Hex(bytes.into()))))
+    }
+
+    fn push_pc(&self, block_name: &str) -> RItem {
+        self.function(RFunctionArg::VariableWithField(RVariableWithField {
+            variable: self.located(RVariable(block_name.to_owned())),
+            field: self.located(RVariable("pc".to_owned())),
+        }))
+    }
+
+    fn function(&self, arg: RFunctionArg) -> RItem {
+        self.item(
+            RFunction {
+                name: self.located(RVariable("push".to_owned())),
+                arg: self.located(arg),
+            }
+            .into(),
+        )
+    }
+
+    fn item(&self, item: RBlockItem) -> RItem {
+        self.located(WithAttributes {
+            attributes: Vec::new(),
+            inner: self.located(item),
+        })
+    }
+
+    fn located<T>(&self, inner: T) -> Located<T> {
+        Located {
+            location: self.location.clone(),
+            inner,
+        }
+    }
+}
+
+fn builder_block(location: &Location, name: String, items: Vec<RItem>) -> RBlockWithAttr {
+    Located {
+        location: location.clone(),
+        inner: WithAttributes {
+            attributes: Vec::new(),
+            inner: Located {
+                location: location.clone(),
+                inner: RBlock {
+                    name: Located {
+                        location: location.clone(),
+                        inner: RVariable(name),
+                    },
+                    abstr: false,
+                    data: false,
+                    signature: None,
+                    items,
+                },
+            },
+        },
+    }
+}
+
+fn trim_leading_zeros(bytes: &[u8]) -> Bytes {
+    let start = bytes.iter().position(|byte| *byte != 0).unwrap_or(bytes.len());
+    bytes[start..].to_vec().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compile::file::compile_source;
+    use crate::compile::settings::{CompilerSettings, DispatchStrategy};
+    use crate::diagnostics::codes::Code;
+
+    /// More selectors than `BINARY_SEARCH_LEAF_SIZE`.
+    const INPUT: &str = "\
+contract A {
+    #[dispatch]
+    block main { callvalue push(f.pc) jumpi }
+    #[selector(\"a()\")]
+    block a { jumpdest push(0x01) stop }
+    #[selector(\"b()\")]
+    block b { jumpdest push(0x02) stop }
+    #[selector(\"c()\")]
+    block c { jumpdest push(0x03) stop }
+    #[selector(\"d()\")]
+    block d { jumpdest push(0x04) stop }
+    #[selector(\"e()\")]
+    block e { jumpdest push(0x05) stop }
+    #[fallback]
+    block f { jumpdest push(0x) push(0x) revert }
+}";
+
+    const _: () = assert!(super::BINARY_SEARCH_LEAF_SIZE < 5);
+
+    fn compile(input: &str, strategy: DispatchStrategy) -> Result<String, Code> {
+        let settings = CompilerSettings {
+            dispatcher: strategy,
+            ..Default::default()
+        };
+        match compile_source("a.mep", input, "A", settings) {
+            Ok((artifacts, _)) => Ok(hex::encode(&artifacts.contracts["A"].bytecode)),
+            Err(report) => Err(report.diagnostics.iter().next().unwrap().code),
+        }
+    }
+
+    fn check_duplicate_selector(strategy: DispatchStrategy) {
+        let input = INPUT.replace("selector(\"e()\")", "selector(\"a()\")");
+        assert_eq!(compile(&input, strategy), Err(Code::InvalidDispatch));
+    }
+
+    #[test]
+    pub fn test_linear_dispatcher() {
+        let expected = "\
+34610045575f3560e01c80630dbe671f146100495780634df7e3d01461004d578063c3da42b8146100515780638a054ac2146100555780\
+63ffae15ba1461005957610045565b5f5ffd5b6001005b6002005b6003005b6004005b600500";
+        assert_eq!(compile(INPUT, DispatchStrategy::Linear).unwrap(), expected);
+        check_duplicate_selector(DispatchStrategy::Linear);
+    }
+
+    /// The selectors are sorted - `a`, `b`, `d`, `c`, `e` - and the ones lower than the pivot `d` are compared in
+    /// the block `main.lower_0`.
+    #[test]
+    pub fn test_binary_search_dispatcher() {
+        let expected = "\
+3461003a575f3560e01c80638a054ac2116100465780638a054ac214610061578063c3da42b814610065578063ffae15ba1461006957\
+61003a565b5f5ffd5b6001005b6002005b80630dbe671f1461003e5780634df7e3d0146100425761003a565b6004005b6003005b600500";
+        assert_eq!(compile(INPUT, DispatchStrategy::BinarySearch).unwrap(), expected);
+        check_duplicate_selector(DispatchStrategy::BinarySearch);
+    }
+
+    /// The 8 entries of the table at `0x82` are `push2` of 3 bytes, whose argument is copied in memory at `0x1e`:
+    /// `b` and `c` are in the bucket 0, `d` and `e` in the bucket 2, `a` in the bucket 7.
+    #[test]
+    pub fn test_jump_table_dispatcher() {
+        let expected = "\
+34610024575f3560e01c60028160071660030261008201600101601e395f5161ffff16565b5f5ffd5b6002005b6003005b80634df7e3d0\
+14610028578063c3da42b81461002c57610024565b6004005b6005005b80638a054ac21461004b578063ffae15ba1461004f5761002456\
+5b6001005b80630dbe671f1461006e5761002456610030610024610053610024610024610024610024610072";
+        assert_eq!(compile(INPUT, DispatchStrategy::JumpTable).unwrap(), expected);
+        check_duplicate_selector(DispatchStrategy::JumpTable);
+    }
+}
@@ -1,8 +1,12 @@
 pub mod attribute;
+pub mod cfg;
 pub mod opcode;
 pub mod pre_processing;
 
+mod abi;
 mod block_flow;
 mod dependencies;
+mod dispatcher;
 mod queue;
 mod remapping;
+mod stack;
@@ -2,6 +2,8 @@ pub type OpCode = u8;
 
 // pub const PUSH_GAS: usize = 3;
 
+pub const NOT: OpCode = 0x19;
+
 // 0x30 range - closure state.
 pub const ADDRESS: OpCode = 0x30;
 pub const BALANCE: OpCode = 0x31;
@@ -57,6 +59,134 @@ pub const PUSH1: OpCode = 0x60;
 pub const PUSH2: OpCode = 0x61;
 pub const PUSH32: OpCode = 0x7f;
 
+pub const DUP1: OpCode = 0x80;
+
+pub const RETURN: OpCode = 0xf3;
+pub const INVALID: OpCode = 0xfe;
+
+/// Names of the opcodes which can be used in a block, as accepted by `str_to_op`.
+pub const OPCODE_NAMES: &[&str] = &[
+    "stop",
+    "add",
+    "mul",
+    "sub",
+    "div",
+    "sdiv",
+    "mod",
+    "smod",
+    "addmod",
+    "mulmod",
+    "exp",
+    "signextend",
+    "lt",
+    "gt",
+    "slt",
+    "sgt",
+    "eq",
+    "iszero",
+    "and",
+    "or",
+    "xor",
+    "not",
+    "byte",
+    "shl",
+    "shr",
+    "sar",
+    "keccak256",
+    "address",
+    "balance",
+    "origin",
+    "caller",
+    "callvalue",
+    "calldataload",
+    "calldatasize",
+    "calldatacopy",
+    "codesize",
+    "codecopy",
+    "gasprice",
+    "extcodesize",
+    "extcodecopy",
+    "returndatasize",
+    "returndatacopy",
+    "extcodehash",
+    "blockhash",
+    "coinbase",
+    "timestamp",
+    "number",
+    "difficulty",
+    "random",
+    "prevrandao",
+    "gaslimit",
+    "chainid",
+    "selfbalance",
+    "basefee",
+    "blobhash",
+    "blobbasefee",
+    "pop",
+    "mload",
+    "mstore",
+    "mstore8",
+    "sload",
+    "sstore",
+    "jump",
+    "jumpi",
+    "pc",
+    "msize",
+    "gas",
+    "jumpdest",
+    "tload",
+    "tstore",
+    "mcopy",
+    "push0",
+    "dup1",
+    "dup2",
+    "dup3",
+    "dup4",
+    "dup5",
+    "dup6",
+    "dup7",
+    "dup8",
+    "dup9",
+    "dup10",
+    "dup11",
+    "dup12",
+    "dup13",
+    "dup14",
+    "dup15",
+    "dup16",
+    "swap1",
+    "swap2",
+    "swap3",
+    "swap4",
+    "swap5",
+    "swap6",
+    "swap7",
+    "swap8",
+    "swap9",
+    "swap10",
+    "swap11",
+    "swap12",
+    "swap13",
+    "swap14",
+    "swap15",
+    "swap16",
+    "log0",
+    "log1",
+    "log2",
+    "log3",
+    "log4",
+    "create",
+    "call",
+    "callcode",
+    "return",
+    "delegatecall",
+    "create2",
+    "staticcall",
+    "revert",
+    "invalid",
+    "selfdestruct",
+];
+
 pub fn str_to_op(name: &str) -> Option<OpCode> {
     Some(match name.to_lowercase().as_str() {
         // 0x0 range - arithmetic ops.
@@ -199,6 +329,7 @@ pub fn str_to_op(name: &str) -> Option<OpCode> {
 
         "staticcall" => 0xfa,
         "revert" => 0xfd,
+        "invalid" => INVALID,
 
         "selfdestruct" => 0xff,
 
@@ -206,8 +337,54 @@ pub fn str_to_op(name: &str) -> Option<OpCode> {
     })
 }
 
+/// Name of an opcode, `None` for the bytes which are not opcodes.
+pub fn op_to_str(op: OpCode) -> Option<String> {
+    match op {
+        PREVRANDAO => Some("prevrandao".to_owned()),
+        PUSH1..=PUSH32 => Some(format!("push{}", op - PUSH0)),
+        _ => OPCODE_NAMES
+            .iter()
+            .find(|name| str_to_op(name) == Some(op))
+            .map(|name| (*name).to_owned()),
+    }
+}
+
+/// Number of items an opcode takes from the stack and puts on it, `None` for the bytes which are not opcodes. A
+/// `dup` or a `swap` takes the items it reads, and puts them back.
+#[rustfmt::skip]
+pub fn stack_io(op: OpCode) -> Option<(usize, usize)> {
+    Some(match op {
+        0x00 => (0, 0),
+        0x01..=0x07 | 0x0a | 0x0b => (2, 1),
+        0x08 | 0x09 => (3, 1),
+        0x10..=0x14 | 0x16..=0x18 | 0x1a..=0x1d => (2, 1),
+        0x15 | NOT => (1, 1),
+        0x20 => (2, 1),
+        BALANCE | CALLDATALOAD | EXTCODESIZE | EXTCODEHASH | BLOCKHASH | BLOBHASH | MLOAD | SLOAD | TLOAD => (1, 1),
+        ADDRESS | ORIGIN | CALLER | CALLVALUE | CALLDATASIZE | CODESIZE | GASPRICE | RETURNDATASIZE => (0, 1),
+        COINBASE..=BASEFEE | BLOBBASEFEE | PC | MSIZE | GAS => (0, 1),
+        CALLDATACOPY | CODECOPY | RETURNDATACOPY | MCOPY => (3, 0),
+        EXTCODECOPY => (4, 0),
+        POP | JUMP => (1, 0),
+        MSTORE | MSTORE8 | SSTORE | TSTORE | JUMPI => (2, 0),
+        JUMPDEST => (0, 0),
+        PUSH0..=PUSH32 => (0, 1),
+        DUP1..=0x8f => ((op - DUP1 + 1) as usize, (op - DUP1 + 2) as usize),
+        0x90..=0x9f => ((op - 0x90 + 2) as usize, (op - 0x90 + 2) as usize),
+        0xa0..=0xa4 => ((op - 0xa0 + 2) as usize, 0),
+        0xf0 => (3, 1),
+        0xf1 | 0xf2 => (7, 1),
+        RETURN | 0xfd => (2, 0),
+        0xf4 | 0xfa => (6, 1),
+        0xf5 => (4, 1),
+        INVALID => (0, 0),
+        0xff => (1, 0),
+        _ => return None,
+    })
+}
+
 pub fn push_length(op: u8) -> Option<usize> {
-    if PUSH0 <= op && op <= PUSH32 {
+    if (PUSH0..=PUSH32).contains(&op) {
         Some((op - PUSH0) as usize)
     } else {
         None
@@ -1,41 +1,52 @@
+use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
 use std::ops::Deref;
 
 use bytes::Bytes;
 
+use super::abi::build_abi;
 use super::attribute::Attribute;
 use super::block_flow::{
-    analyze_block_flow,
-    is_function_name,
-    BlockFlow,
-    BlockFlowBlockRef,
-    BlockFlowItem,
-    BlockFlowPush,
-    BlockFlowPushInner,
+    analyze_block_flow, is_function_name, resolve_hex_alias, BlockFlow, BlockFlowBlockRef, BlockFlowItem,
+    BlockFlowPush, BlockFlowPushInner,
 };
+use super::cfg::apply_cfg;
+use super::dispatcher::{expand_dispatcher, DispatchEntry};
 use super::opcode::str_to_op;
 use super::queue::PersistentDedupQueue;
 use super::remapping::remap_contracts;
+use super::stack::{check_stack_signatures, StackSignature};
+use crate::abi::entry::{AbiEntry, AbiFunction};
 use crate::ast::*;
-use crate::parser::error::{new_error_from_located, new_error_from_location, new_generic_error};
-use crate::parser::parser::{Located, Rule};
+use crate::compile::settings::CompilerSettings;
+use crate::diagnostics::codes::Code;
+use crate::diagnostics::diagnostic::{Diagnostic, Diagnostics};
+use crate::parser::parser::{Located, Location};
 use crate::pre_processing::attribute::Attributes;
 use crate::pre_processing::dependencies::DepsGraph;
 use crate::pre_processing::remapping::remap_blocks;
 use crate::types::bytes32::Bytes32;
+use crate::types::hash::keccak256;
 
 #[derive(Clone, Default, Debug)]
 pub struct Contract {
     pub blocks: Vec<Block>,
     pub name: String,
+    pub abi: Vec<AbiEntry>,
     #[allow(unused)]
     pub last: bool,
+    pub initcode: bool,
+    /// Warnings that are not reported for this contract.
+    pub allowed: HashSet<Code>,
 }
 
 #[derive(Clone, Default, Debug)]
 pub struct Block {
     pub name: String,
     pub items: Vec<BlockItem>,
+    pub data: bool,
+    /// Warnings that are not reported for this block.
+    pub allowed: HashSet<Code>,
 }
 
 #[derive(Clone, Debug)]
@@ -43,6 +54,19 @@ pub struct BlockItem {
     pub inner: BlockItemInner,
     pub start_names: Vec<String>,
     pub end_names: Vec<String>,
+    /// Abstract blocks whose copy starts with this item.
+    pub abstract_names: Vec<String>,
+    /// Parts of the source the bytes of the item come from. Empty for the generated items.
+    pub sources: Vec<ItemSource>,
+}
+
+impl BlockItem {
+    fn with_sources(inner: BlockItemInner, sources: &[ItemSource]) -> Self {
+        Self {
+            sources: sources.to_vec(),
+            ..inner.into()
+        }
+    }
 }
 
 impl From<BlockItemInner> for BlockItem {
@@ -51,10 +75,19 @@ impl From<BlockItemInner> for BlockItem {
             inner,
             start_names: Vec::new(),
             end_names: Vec::new(),
+            abstract_names: Vec::new(),
+            sources: Vec::new(),
         }
     }
 }
 
+/// Part of the source of the bytes of a block item, starting at the byte `offset` of the item.
+#[derive(Clone, Debug)]
+pub struct ItemSource {
+    pub offset: usize,
+    pub location: Location,
+}
+
 impl Deref for BlockItem {
     type Target = BlockItemInner;
 
@@ -65,7 +98,10 @@ impl Deref for BlockItem {
 
 #[derive(Clone, Debug)]
 pub enum BlockItemInner {
+    /// Opcodes.
     Bytes(Bytes),
+    /// Raw bytes, emitted as is.
+    Data(Bytes),
     Contract(usize),
     Push(Push),
 }
@@ -79,39 +115,81 @@ pub struct Push {
 #[derive(Clone, Debug)]
 pub enum PushInner {
     Constant(Bytes32),
-    BlockSize { index: usize, start: usize, end: usize },
-    BlockPc { index: usize, line: usize },
+    BlockSize {
+        index: usize,
+        start: usize,
+        end: usize,
+    },
+    BlockPc {
+        index: usize,
+        line: usize,
+    },
+    ContractSize(usize),
+    ContractCodehash(usize),
+    Create2 {
+        deployer: [u8; 20],
+        salt: Bytes32,
+        index: usize,
+    },
 }
 
 pub fn pre_process(
-    input: &str,
-    r_file: RFile,
+    mut r_file: RFile,
     contract_name: &str,
-    compile_variables: &HashMap<String, Bytes>,
-) -> Result<Vec<Contract>, pest::error::Error<Rule>> {
+    settings: &CompilerSettings,
+    warnings: &mut Diagnostics,
+) -> Result<Vec<Contract>, Diagnostics> {
+    let compile_variables = &settings.variables;
+    let declared = r_file
+        .0
+        .iter()
+        .any(|r_contract| r_contract.inner().name_str() == contract_name);
+    apply_cfg(&mut r_file, compile_variables)?;
+
+    let mut errors = Diagnostics::default();
     let mut main_index: Option<usize> = None;
     let mut contract_names = HashMap::<String, usize>::new();
     let mut contract_attributes = vec![Attributes::default(); r_file.0.len()];
+    let mut contract_abis = vec![Vec::<Located<AbiEntry>>::new(); r_file.0.len()];
+    let mut contract_initcodes = vec![false; r_file.0.len()];
 
     for contract_index in 0..r_file.0.len() {
         let r_contract_with_attr = &r_file.0[contract_index];
         for r_attribute in &r_contract_with_attr.attributes {
-            let attribute = Attribute::from_r_attribute(input, r_attribute, compile_variables)?;
-            if attribute.is_contract_attribute() {
+            let attribute = match Attribute::from_r_attribute(r_attribute, compile_variables) {
+                Ok(attribute) => attribute,
+                Err(err) => {
+                    errors.push(err);
+                    continue;
+                },
+            };
+            if let Attribute::Abi(entry) = attribute {
+                contract_abis[contract_index].push(Located {
+                    location: r_attribute.location.clone(),
+                    inner: entry,
+                });
+            } else if attribute.is_initcode() {
+                contract_initcodes[contract_index] = true;
+            } else if attribute.is_contract_attribute() {
                 contract_attributes[contract_index].apply(attribute);
             } else {
-                return Err(new_error_from_located(input, r_attribute, "Invalid contract attribute"));
+                errors.push(Diagnostic::new(Code::InvalidAttribute, "Invalid contract attribute").at(r_attribute));
             }
         }
 
         let r_contract = &r_contract_with_attr.inner().inner;
         let name = r_contract.name_str();
         if contract_names.insert(name.to_owned(), contract_names.len()).is_some() {
-            return Err(new_error_from_located(
-                input,
-                &r_contract.name,
-                &format!("Name `{}` already used", name),
-            ));
+            let previous = r_file
+                .0
+                .iter()
+                .find(|r_contract| r_contract.inner().name_str() == name)
+                .unwrap();
+            errors.push(
+                Diagnostic::new(Code::NameAlreadyUsed, format!("Name `{}` already used", name))
+                    .at(&r_contract.name)
+                    .with_label(&previous.inner().name.location, "first declared here"),
+            );
         }
         if name == contract_name {
             // cannot happen twice
@@ -120,7 +198,15 @@ pub fn pre_process(
     }
 
     let Some(main_index) = main_index else {
-        return Err(new_generic_error(format!("Contract `{}` not found", contract_name)));
+        let mut err = Diagnostic::new(
+            Code::MissingEntryPoint,
+            format!("Contract `{}` not found", contract_name),
+        );
+        if declared {
+            err = err.with_note("The contract is disabled by a `cfg` attribute.");
+        }
+        errors.push(err);
+        return Err(errors);
     };
 
     let mut contracts = HashMap::<usize, Contract>::new();
@@ -132,31 +218,44 @@ pub fn pre_process(
     while let Some(index_to_process) = contracts_queue.pop() {
         // tracing::info!("Pre-processing contract {}", &r_file.0[index_to_process].inner().name_str());
         contracts_dependency_tree.add_node_if_needed(&index_to_process);
-        let (contract, dependencies) = pre_process_contract(
-            input,
+        // the dependencies found before an error are still pre-processed, to report their errors too
+        let mut dependencies = HashSet::<usize>::new();
+        let result = pre_process_contract(
             &r_file.0[index_to_process],
             &contract_attributes[index_to_process],
+            &contract_abis[index_to_process],
             &contract_names,
-            compile_variables,
-        )?;
+            settings,
+            &mut dependencies,
+            warnings,
+        );
 
         for dependency in dependencies {
             contracts_queue.insert_if_needed(dependency);
             contracts_dependency_tree.insert_if_needed(&index_to_process, &dependency);
         }
 
-        contracts.insert(index_to_process, contract);
+        match result {
+            Ok(mut contract) => {
+                contract.initcode = contract_initcodes[index_to_process];
+                contracts.insert(index_to_process, contract);
+            },
+            Err(contract_errors) => errors.extend(contract_errors),
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
     }
 
-    for index in 0..r_file.0.len() {
-        if contracts.get(&index).is_none() {
-            tracing::warn!(
-                "{}",
-                new_error_from_located(
-                    input,
-                    r_file.0[index].inner(),
-                    &format!("Unused contract `{}`", r_file.0[index].inner().name_str())
+    for (index, (r_contract, attributes)) in r_file.0.iter().zip(&contract_attributes).enumerate() {
+        if !contracts.contains_key(&index) && !attributes.allowed.contains(&Code::UnusedContract) {
+            warnings.push(
+                Diagnostic::new(
+                    Code::UnusedContract,
+                    format!("Unused contract `{}`", r_contract.inner().name_str()),
                 )
+                .at(r_contract.inner()),
             );
         }
     }
@@ -167,7 +266,7 @@ pub fn pre_process(
     }
 
     if !contracts_dependency_tree.is_empty() {
-        return Err(new_generic_error("Recursive contracts unhandled".to_owned()));
+        return Err(Diagnostic::new(Code::RecursiveReference, "Recursive contracts unhandled".to_owned()).into());
     }
 
     remapping_indexes.reverse();
@@ -176,64 +275,154 @@ pub fn pre_process(
 }
 
 pub fn pre_process_contract(
-    input: &str,
     r_contract_with_attr: &Located<WithAttributes<Located<RContract>>>,
     default_attributes: &Attributes,
+    contract_abi: &[Located<AbiEntry>],
     contract_names: &HashMap<String, usize>,
-    compile_variables: &HashMap<String, Bytes>,
-) -> Result<(Contract, HashSet<usize>), pest::error::Error<Rule>> {
+    settings: &CompilerSettings,
+    contract_dependencies: &mut HashSet<usize>,
+    warnings: &mut Diagnostics,
+) -> Result<Contract, Diagnostics> {
+    let compile_variables = &settings.variables;
     let r_contract = &r_contract_with_attr.inner.inner;
 
-    let constants = extract_constants(input, &r_contract.constants, contract_names, compile_variables)?;
+    let mut errors = Diagnostics::default();
+    let constants = extract_constants(&r_contract.constants, contract_names, compile_variables, &mut errors);
 
     let mut block_attributes = vec![Vec::<Attribute>::new(); r_contract.blocks.len()];
+    let mut block_allowed = vec![HashSet::<Code>::new(); r_contract.blocks.len()];
+    let mut signatures = vec![None::<StackSignature>; r_contract.blocks.len()];
 
     let mut main_index: Option<usize> = None;
     let mut last_index: Option<usize> = None;
+    let mut dispatch_index: Option<usize> = None;
+    let mut fallback_index: Option<usize> = None;
+    let mut dispatch_entries = Vec::<DispatchEntry>::new();
+    let mut dispatched_functions = Vec::<Located<AbiFunction>>::new();
+    let mut abi_entries = contract_abi.to_vec();
     let mut block_names = HashMap::<String, usize>::new();
 
     let mut blocks_queue = PersistentDedupQueue::<usize>::new();
 
     for block_index in 0..r_contract.blocks.len() {
         let r_block_with_attr = &r_contract.blocks[block_index];
+        if let Some(signature) = &r_block_with_attr.inner().signature {
+            if r_block_with_attr.inner().data {
+                errors.push(
+                    Diagnostic::new(Code::InvalidDataBlock, "A data block cannot have a stack signature.")
+                        .at(signature),
+                );
+            }
+            signatures[block_index] = Some(StackSignature {
+                inputs: signature.inputs,
+                outputs: signature.outputs,
+                location: signature.location.clone(),
+            });
+        }
         for r_attribute in &r_block_with_attr.attributes {
-            let attribute = Attribute::from_r_attribute(input, r_attribute, compile_variables)?;
-            if !r_block_with_attr.inner().abstr {
+            let attribute = match Attribute::from_r_attribute(r_attribute, compile_variables) {
+                Ok(attribute) => attribute,
+                Err(err) => {
+                    errors.push(err);
+                    continue;
+                },
+            };
+            if let Attribute::Allow(code) = attribute {
+                block_allowed[block_index].insert(code);
+            } else if let (Attribute::Stack { inputs, outputs }, false) = (&attribute, r_block_with_attr.inner().data) {
+                let signature = StackSignature {
+                    inputs: *inputs,
+                    outputs: *outputs,
+                    location: r_attribute.location.clone(),
+                };
+                if let Some(previous) = signatures[block_index].replace(signature) {
+                    errors.push(
+                        Diagnostic::new(Code::InvalidAttribute, "This block has already a stack signature.")
+                            .at(r_attribute)
+                            .with_label(&previous.location, "also declared here"),
+                    );
+                }
+            } else if r_block_with_attr.inner().data {
+                if attribute.is_keep() {
+                    blocks_queue.insert_if_needed(block_index);
+                } else {
+                    errors
+                        .push(Diagnostic::new(Code::InvalidDataBlock, "Invalid data block attribute.").at(r_attribute));
+                }
+            } else if !r_block_with_attr.inner().abstr {
                 if attribute.is_block_attribute() {
                     if attribute.is_last() {
                         blocks_queue.insert_if_needed(block_index);
-                        if last_index.replace(block_index).is_some() {
-                            return Err(new_error_from_located(
-                                input,
-                                r_attribute,
-                                "This contract has already a block marked with the attribute `last`.",
-                            ));
+                        if let Some(previous) = last_index.replace(block_index) {
+                            errors.push(
+                                Diagnostic::new(
+                                    Code::DuplicateMarker,
+                                    "This contract has already a block marked with the attribute `last`.",
+                                )
+                                .at(r_attribute)
+                                .with_label(&r_contract.blocks[previous].name.location, "previously marked here"),
+                            );
                         }
                     } else if attribute.is_keep() {
                         blocks_queue.insert_if_needed(block_index);
                     } else if attribute.is_main() {
-                        if main_index.replace(block_index).is_some() {
-                            return Err(new_error_from_located(
-                                input,
-                                r_attribute,
-                                "A block is already marked as main.",
-                            ));
+                        if let Some(previous) = main_index.replace(block_index) {
+                            errors.push(
+                                Diagnostic::new(Code::DuplicateMarker, "A block is already marked as main.")
+                                    .at(r_attribute)
+                                    .with_label(&r_contract.blocks[previous].name.location, "previously marked here"),
+                            );
                         }
+                    } else if attribute.is_dispatch() {
+                        if let Some(previous) = dispatch_index.replace(block_index) {
+                            errors.push(
+                                Diagnostic::new(
+                                    Code::DuplicateMarker,
+                                    "This contract has already a block marked with the attribute `dispatch`.",
+                                )
+                                .at(r_attribute)
+                                .with_label(&r_contract.blocks[previous].name.location, "previously marked here"),
+                            );
+                        }
+                    } else if attribute.is_fallback() {
+                        if let Some(previous) = fallback_index.replace(block_index) {
+                            errors.push(
+                                Diagnostic::new(
+                                    Code::DuplicateMarker,
+                                    "This contract has already a block marked with the attribute `fallback`.",
+                                )
+                                .at(r_attribute)
+                                .with_label(&r_contract.blocks[previous].name.location, "previously marked here"),
+                            );
+                        }
+                    } else if let Attribute::Selector(function) = attribute {
+                        dispatch_entries.push(DispatchEntry {
+                            selector: function.selector(),
+                            block_index,
+                            location: r_attribute.location.clone(),
+                        });
+                        dispatched_functions.push(Located {
+                            location: r_attribute.location.clone(),
+                            inner: function,
+                        });
+                    } else if let Attribute::Abi(entry) = attribute {
+                        abi_entries.push(Located {
+                            location: r_attribute.location.clone(),
+                            inner: entry,
+                        });
                     } else {
                         block_attributes[block_index].push(attribute);
                     }
                 } else {
-                    return Err(new_error_from_located(input, r_attribute, "Invalid block attribute."));
+                    errors.push(Diagnostic::new(Code::InvalidAttribute, "Invalid block attribute.").at(r_attribute));
                 }
             } else {
                 if attribute.is_abstract_block_attribute() {
                     block_attributes[block_index].push(attribute);
                 } else {
-                    return Err(new_error_from_located(
-                        input,
-                        r_attribute,
-                        "Invalid abstract block attribute.",
-                    ));
+                    errors.push(
+                        Diagnostic::new(Code::InvalidAttribute, "Invalid abstract block attribute.").at(r_attribute),
+                    );
                 }
             }
         }
@@ -245,57 +434,112 @@ pub fn pre_process_contract(
             || constants.contains_key(block_name)
             || block_names.insert(block_name.to_owned(), block_names.len()).is_some()
         {
-            return Err(new_error_from_located(
-                input,
-                &r_block.name,
-                &format!("Name `{}` already used", block_name),
-            ));
+            let mut diagnostic =
+                Diagnostic::new(Code::NameAlreadyUsed, format!("Name `{}` already used", block_name)).at(&r_block.name);
+            if let Some(previous) = r_contract.blocks[..block_index]
+                .iter()
+                .find(|r_block| r_block.inner().name_str() == block_name)
+            {
+                diagnostic = diagnostic.with_label(&previous.inner().name.location, "first declared here");
+            }
+            errors.push(diagnostic);
         }
-        if block_name == "main" && main_index.replace(block_index).is_some() {
-            return Err(new_error_from_located(
-                input,
-                &r_block.name,
-                "A block is already marked as main.",
-            ));
+        if block_name == "main" && r_block.data {
+            errors.push(
+                Diagnostic::new(Code::InvalidDataBlock, "The block `main` cannot be a data block.").at(&r_block.name),
+            );
+        }
+        if block_name == "main" {
+            if let Some(previous) = main_index.replace(block_index) {
+                errors.push(
+                    Diagnostic::new(Code::DuplicateMarker, "A block is already marked as main.")
+                        .at(&r_block.name)
+                        .with_label(&r_contract.blocks[previous].name.location, "previously marked here"),
+                );
+            }
         }
-        if r_block.items.is_empty() {
-            return Err(new_error_from_located(
-                input,
-                &r_block.name,
-                "A block must not be empty.",
-            ));
+        if r_block.items.is_empty() && dispatch_index != Some(block_index) {
+            errors.push(Diagnostic::new(Code::EmptyBlock, "A block must not be empty.").at(&r_block.name));
         }
     }
 
+    let r_blocks = if let Some(dispatch_index) = dispatch_index {
+        let r_blocks = match expand_dispatcher(
+            &r_contract.blocks,
+            dispatch_index,
+            fallback_index,
+            dispatch_entries,
+            settings.dispatcher,
+            &mut block_names,
+        ) {
+            Ok(r_blocks) => r_blocks,
+            Err(err) => {
+                errors.push(err);
+                return Err(errors);
+            },
+        };
+        block_attributes.resize(r_blocks.len(), Vec::new());
+        block_allowed.resize(r_blocks.len(), HashSet::new());
+        signatures.resize(r_blocks.len(), None);
+        Cow::Owned(r_blocks)
+    } else {
+        if let Some(entry) = dispatch_entries.first() {
+            errors.push(
+                Diagnostic::new(
+                    Code::InvalidDispatch,
+                    "No block marked with the attribute `dispatch` in this contract.",
+                )
+                .at_location(&entry.location),
+            );
+        }
+        if let Some(fallback_index) = fallback_index {
+            errors.push(
+                Diagnostic::new(
+                    Code::InvalidDispatch,
+                    "No block marked with the attribute `dispatch` in this contract.",
+                )
+                .at(&r_contract.blocks[fallback_index].name),
+            );
+        }
+        Cow::Borrowed(&r_contract.blocks)
+    };
+
+    let abi = build_abi(abi_entries, dispatched_functions).unwrap_or_else(|err| {
+        errors.push(err);
+        Vec::new()
+    });
+
     let main_index = main_index;
     let last_index = last_index;
     let block_attributes = block_attributes;
     let block_names = block_names;
 
     let Some(main_index) = main_index else {
-        return Err(new_error_from_located(
-            input,
-            &r_contract,
-            &format!("Block `main` not found in contract `{}`", r_contract.name_str()),
-        ));
+        errors.push(
+            Diagnostic::new(
+                Code::MissingEntryPoint,
+                format!("Block `main` not found in contract `{}`", r_contract.name_str()),
+            )
+            .at(r_contract),
+        );
+        return Err(errors);
     };
     blocks_queue.insert_if_needed(main_index);
 
     let mut blocks_flow = HashMap::<usize, BlockFlow>::new();
-    let mut contract_dependencies = HashSet::<usize>::new();
     let mut block_dependency_tree = DepsGraph::<usize>::new();
 
     while let Some(index_to_process) = blocks_queue.pop() {
         block_dependency_tree.add_node_if_needed(&index_to_process);
         let block = analyze_block_flow(
-            input,
-            &r_contract.blocks[index_to_process],
+            &r_blocks[index_to_process],
             &constants,
-            &contract_names,
+            contract_names,
             &block_names,
-            &mut contract_dependencies,
+            contract_dependencies,
             compile_variables,
-        )?;
+            &mut errors,
+        );
 
         for strong_dep in &block.strong_deps {
             blocks_queue.insert_if_needed(*strong_dep);
@@ -309,15 +553,20 @@ pub fn pre_process_contract(
     }
     let blocks_flow = blocks_flow;
 
-    for block_index in 0..r_contract.blocks.len() {
-        if blocks_flow.get(&block_index).is_none() {
-            tracing::warn!(
-                "{}",
-                new_error_from_located(
-                    input,
-                    &r_contract.blocks[block_index],
-                    &format!("Unused block `{}`", &r_contract.blocks[block_index].inner().name_str())
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    for block_index in 0..r_blocks.len() {
+        let allowed = default_attributes.allowed.contains(&Code::UnusedBlock)
+            || block_allowed[block_index].contains(&Code::UnusedBlock);
+        if !blocks_flow.contains_key(&block_index) && !allowed {
+            warnings.push(
+                Diagnostic::new(
+                    Code::UnusedBlock,
+                    format!("Unused block `{}`", r_blocks[block_index].inner().name_str()),
                 )
+                .at(&r_blocks[block_index]),
             );
         }
     }
@@ -328,7 +577,12 @@ pub fn pre_process_contract(
     while block_dependency_tree.pop_leaf().is_some() {}
 
     if !block_dependency_tree.is_empty() {
-        return Err(new_generic_error("Recursive blocks unhandled".to_owned()));
+        return Err(Diagnostic::new(Code::RecursiveReference, "Recursive blocks unhandled".to_owned()).into());
+    }
+
+    let errors = check_stack_signatures(&r_blocks, &blocks_flow, &signatures);
+    if !errors.is_empty() {
+        return Err(errors);
     }
 
     let mut blocks = HashMap::<usize, Block>::new();
@@ -336,16 +590,18 @@ pub fn pre_process_contract(
     let mut new_positions = HashMap::<usize, BlockPosition>::new();
     let mut remapping = Vec::<usize>::new();
     remapping.push(main_index);
+    let mut data_remapping = Vec::<usize>::new();
 
     while let Some(index_to_process) = blocks_queue.pop() {
-        if index_to_process != main_index && index_to_process != last_index.unwrap_or(main_index) {
+        if r_blocks[index_to_process].inner().data {
+            data_remapping.push(index_to_process);
+        } else if index_to_process != main_index && index_to_process != last_index.unwrap_or(main_index) {
             remapping.push(index_to_process);
         }
 
-        let block = pre_process_block(
-            input,
+        let mut block = pre_process_block(
             index_to_process,
-            &r_contract.blocks,
+            &r_blocks,
             &blocks_flow,
             BlockPreProcessingContext::new_root(index_to_process),
             &mut [index_to_process].into(),
@@ -354,61 +610,102 @@ pub fn pre_process_contract(
             &mut unique_dereferences,
             &mut new_positions,
         )?;
+        block.allowed = block_allowed[index_to_process].clone();
 
         blocks.insert(index_to_process, block);
     }
     if let Some(last_index) = last_index {
+        // the data blocks are placed after the code, so a block marked `last` would not be at the end
+        if let Some(data_index) = data_remapping.first() {
+            return Err(Diagnostic::new(
+                Code::InvalidAttribute,
+                "A block cannot be marked with the attribute `last` in a contract with data blocks.",
+            )
+            .at(&r_blocks[last_index].name)
+            .with_label(&r_blocks[*data_index].name.location, "data block declared here")
+            .with_note("The data blocks are placed at the end of the bytecode.")
+            .into());
+        }
         remapping.push(last_index);
     }
-
-    Ok((
-        Contract {
-            name: r_contract.name_str().to_owned(),
-            blocks: remap_blocks(blocks, &remapping, &new_positions),
-            last: last_index.is_some(),
-        },
-        contract_dependencies,
-    ))
+    // the data blocks are placed after the code
+    remapping.append(&mut data_remapping);
+
+    Ok(Contract {
+        name: r_contract.name_str().to_owned(),
+        abi,
+        blocks: remap_blocks(blocks, &remapping, &new_positions),
+        last: last_index.is_some(),
+        initcode: false,
+        allowed: default_attributes.allowed.clone(),
+    })
 }
 
 pub fn extract_constants(
-    input: &str,
-    r_constants: &Vec<Located<RConstant>>,
+    r_constants: &Vec<Located<WithAttributes<Located<RConstant>>>>,
     contract_names: &HashMap<String, usize>,
     compile_variables: &HashMap<String, Bytes>,
-) -> Result<HashMap<String, Bytes>, pest::error::Error<Rule>> {
+    errors: &mut Diagnostics,
+) -> HashMap<String, Bytes> {
     let mut constants = HashMap::<String, Bytes>::new();
 
-    for r_constant in r_constants {
+    for r_constant_with_attr in r_constants {
+        // the `cfg` attributes are already removed
+        for r_attribute in &r_constant_with_attr.attributes {
+            errors.push(Diagnostic::new(Code::InvalidAttribute, "Invalid constant attribute.").at(r_attribute));
+        }
+        let r_constant = r_constant_with_attr.inner();
         let constant_name = r_constant.name_str();
 
         let value = match &r_constant.value.inner {
-            RConstantArg::HexLiteral(hex_literal) => hex_literal.0.clone(),
+            // an invalid constant is kept, empty, so that its uses are not reported as unknown
+            RConstantArg::Negation(negation) => {
+                match resolve_hex_alias(&negation.0, &negation.0.location, &constants, compile_variables)
+                    .and_then(|value| get_negated_value(&value, &r_constant.value.location))
+                {
+                    Ok(value) => value,
+                    Err(err) => {
+                        errors.push(err);
+                        Bytes::new()
+                    },
+                }
+            },
+            RConstantArg::Literal(literal) => match get_literal_value(literal, &r_constant.value.location) {
+                Ok(value) => value,
+                Err(err) => {
+                    errors.push(err);
+                    Bytes::new()
+                },
+            },
             RConstantArg::CompileVariable(compile_variable) => {
-                get_compile_variable_value(input, &compile_variable, compile_variables)?.clone()
+                match get_compile_variable_value(compile_variable, compile_variables) {
+                    Ok(value) => value.clone(),
+                    Err(err) => {
+                        errors.push(err);
+                        Bytes::new()
+                    },
+                }
             },
         };
 
         if contract_names.contains_key(constant_name)
             || constants.insert(constant_name.to_owned(), value.clone()).is_some()
         {
-            return Err(new_error_from_located(
-                input,
-                &r_constant.name,
-                &format!("Name {} already used.", r_constant.name.0),
-            ));
+            errors.push(
+                Diagnostic::new(
+                    Code::NameAlreadyUsed,
+                    format!("Name {} already used.", r_constant.name.0),
+                )
+                .at(&r_constant.name),
+            );
         }
 
         if str_to_op(constant_name).is_some() || is_function_name(constant_name) {
-            return Err(new_error_from_located(
-                input,
-                &r_constant.name,
-                &format!("Invalid constant name."),
-            ));
+            errors.push(Diagnostic::new(Code::InvalidConstantName, "Invalid constant name.").at(&r_constant.name));
         }
     }
 
-    Ok(constants)
+    constants
 }
 
 #[derive(Clone, Debug)]
@@ -444,7 +741,6 @@ impl BlockPreProcessingContext {
 }
 
 fn pre_process_block(
-    input: &str,
     index_to_process: usize,
     r_blocks: &Vec<Located<WithAttributes<Located<RBlock>>>>,
     blocks_flow: &HashMap<usize, BlockFlow>,
@@ -454,7 +750,7 @@ fn pre_process_block(
     block_attributes: &Vec<Vec<Attribute>>,
     unique_dereferences: &mut HashSet<usize>,
     new_positions: &mut HashMap<usize, BlockPosition>,
-) -> Result<Block, pest::error::Error<Rule>> {
+) -> Result<Block, Diagnostic> {
     // tracing::info!("Pre-processing block {}", &r_blocks[index_to_process].inner().name_str());
 
     current_attributes.apply_many(block_attributes[index_to_process].clone());
@@ -463,15 +759,23 @@ fn pre_process_block(
 
     let block_flow = blocks_flow.get(&index_to_process).unwrap();
 
-    for block_flow_item in &block_flow.items {
+    for (block_flow_item, sources) in block_flow.items.iter().zip(&block_flow.sources) {
         match block_flow_item {
-            BlockFlowItem::Bytes(bytes) => items.push(BlockItemInner::Bytes(bytes.clone()).into()),
+            BlockFlowItem::Bytes(bytes) => {
+                items.push(BlockItem::with_sources(BlockItemInner::Bytes(bytes.clone()), sources));
+            },
+            BlockFlowItem::Data(bytes) => {
+                items.push(BlockItem::with_sources(BlockItemInner::Data(bytes.clone()), sources));
+            },
             BlockFlowItem::Contract(contract_index) => {
-                items.push(BlockItemInner::Contract(*contract_index).into());
+                items.push(BlockItem::with_sources(
+                    BlockItemInner::Contract(*contract_index),
+                    sources,
+                ));
             },
             BlockFlowItem::Push(BlockFlowPush { attributes, inner }) => {
                 current_attributes.apply_many(attributes.clone());
-                items.push(
+                items.push(BlockItem::with_sources(
                     BlockItemInner::Push(Push {
                         attributes: current_attributes.clone(),
                         inner: match inner {
@@ -482,10 +786,17 @@ fn pre_process_block(
                                 start: 0,
                                 end: 0,
                             },
+                            BlockFlowPushInner::ContractSize(index) => PushInner::ContractSize(*index),
+                            BlockFlowPushInner::ContractCodehash(index) => PushInner::ContractCodehash(*index),
+                            BlockFlowPushInner::Create2 { deployer, salt, index } => PushInner::Create2 {
+                                deployer: *deployer,
+                                salt: salt.clone(),
+                                index: *index,
+                            },
                         },
-                    })
-                    .into(),
-                );
+                    }),
+                    sources,
+                ));
             },
             BlockFlowItem::BlockEsp(BlockFlowBlockRef {
                 index: block_index,
@@ -493,28 +804,35 @@ fn pre_process_block(
                 attributes,
             }) => {
                 current_attributes.apply_many(attributes.clone());
+                if r_blocks[*block_index].inner().data {
+                    return Err(Diagnostic::new(
+                        Code::InvalidBlockReference,
+                        "A data block can only be referred to by its `pc` or its `size`.",
+                    )
+                    .at_location(location));
+                }
                 if !r_blocks[*block_index].inner().abstr {
-                    return Err(new_error_from_location(
-                        input,
-                        &location,
+                    return Err(Diagnostic::new(
+                        Code::InvalidBlockReference,
                         "Use the `*` to refer to a non abstract block.",
-                    ));
+                    )
+                    .at_location(location));
                 }
 
                 if parents.contains(block_index) {
-                    return Err(new_error_from_location(
-                        input,
-                        &location,
-                        "Recursive block references unhandled",
-                    ));
+                    return Err(
+                        Diagnostic::new(Code::RecursiveReference, "Recursive block references unhandled")
+                            .at_location(location),
+                    );
                 }
 
                 parents.insert(*block_index);
                 let Block {
                     items: mut sub_items,
-                    name: _,
+                    name,
+                    data: _,
+                    allowed: _,
                 } = pre_process_block(
-                    input,
                     *block_index,
                     r_blocks,
                     blocks_flow,
@@ -525,7 +843,10 @@ fn pre_process_block(
                     unique_dereferences,
                     new_positions,
                 )?;
-                parents.remove(&block_index);
+                parents.remove(block_index);
+                if let Some(first) = sub_items.first_mut() {
+                    first.abstract_names.push(name);
+                }
                 items.append(&mut sub_items);
                 current_attributes.apply_many(blocks_flow.get(block_index).unwrap().end_attributes.clone());
             },
@@ -536,27 +857,35 @@ fn pre_process_block(
             }) => {
                 current_attributes.apply_many(attributes.clone());
                 if context.inside_abstract {
-                    return Err(new_error_from_location(
-                        input,
-                        &location,
+                    return Err(Diagnostic::new(
+                        Code::InvalidBlockReference,
                         "Cannot refer to non-abstract block inside an abstract block.",
-                    ));
+                    )
+                    .at_location(location));
                 }
 
                 if r_blocks[*block_index].inner().abstr {
-                    return Err(new_error_from_location(
-                        input,
-                        &location,
+                    return Err(Diagnostic::new(
+                        Code::InvalidBlockReference,
                         "Use the `&` to refer to an abstract block.",
-                    ));
+                    )
+                    .at_location(location));
+                }
+
+                if r_blocks[*block_index].inner().data {
+                    return Err(Diagnostic::new(
+                        Code::InvalidBlockReference,
+                        "A data block can only be referred to by its `pc` or its `size`.",
+                    )
+                    .at_location(location));
                 }
 
                 if unique_dereferences.contains(block_index) {
-                    return Err(new_error_from_location(
-                        input,
-                        &location,
+                    return Err(Diagnostic::new(
+                        Code::InvalidBlockReference,
                         "This non-abtrsact block has already been dereferenced once.",
-                    ));
+                    )
+                    .at_location(location));
                 }
                 // println!("dereferencing {} inside {} (root {})" ,
                 //     r_blocks[*block_index].inner().name_str(),
@@ -566,19 +895,19 @@ fn pre_process_block(
                 unique_dereferences.insert(*block_index);
 
                 if parents.contains(block_index) {
-                    return Err(new_error_from_location(
-                        input,
-                        &location,
-                        "Recursive block references unhandled",
-                    ));
+                    return Err(
+                        Diagnostic::new(Code::RecursiveReference, "Recursive block references unhandled")
+                            .at_location(location),
+                    );
                 }
 
                 parents.insert(*block_index);
                 let Block {
                     items: mut sub_items,
                     name,
+                    data: _,
+                    allowed: _,
                 } = pre_process_block(
-                    input,
                     *block_index,
                     r_blocks,
                     blocks_flow,
@@ -589,7 +918,7 @@ fn pre_process_block(
                     unique_dereferences,
                     new_positions,
                 )?;
-                parents.remove(&block_index);
+                parents.remove(block_index);
                 sub_items.first_mut().unwrap().start_names.push(name.clone());
                 sub_items.last_mut().unwrap().end_names.push(name);
                 items.append(&mut sub_items);
@@ -618,21 +947,122 @@ fn pre_process_block(
     Ok(Block {
         items,
         name: r_blocks[index_to_process].name_str().to_owned(),
+        data: r_blocks[index_to_process].inner().data,
+        allowed: HashSet::new(),
     })
 }
 
 pub fn get_compile_variable_value<'a>(
-    input: &'_ str,
     compile_variable: &'_ RCompileVariable,
     compile_variables: &'a HashMap<String, Bytes>,
-) -> Result<&'a Bytes, pest::error::Error<Rule>> {
+) -> Result<&'a Bytes, Diagnostic> {
     let name = compile_variable.as_str();
     let Some(bytes) = compile_variables.get(name) else {
-        return Err(new_error_from_located(
-            input,
-            &compile_variable.0,
-            &format!("Missing compile time variable {name}. Please specify it in the compiler settings."),
-        ));
+        return Err(Diagnostic::new(
+            Code::MissingCompileVariable,
+            format!("Missing compile time variable {name}. Please specify it in the compiler settings."),
+        )
+        .at(&compile_variable.0));
     };
     Ok(bytes)
 }
+
+/// Two's complement opposite of a value, on 32 bytes.
+pub fn get_negated_value(value: &Bytes, location: &Location) -> Result<Bytes, Diagnostic> {
+    let Some(value) = Bytes32::from_bytes(value, true) else {
+        return Err(Diagnostic::new(Code::InvalidLiteral, "Negated value exceeds 32 bytes.").at_location(location));
+    };
+    Ok(Bytes::copy_from_slice(&(-&value).0))
+}
+
+/// Bytes of a literal. A decimal is written on the fewest bytes, and an address on 20 bytes.
+pub fn get_literal_value(literal: &RLiteral, location: &Location) -> Result<Bytes, Diagnostic> {
+    match literal {
+        RLiteral::Hex(hex_literal) => Ok(hex_literal.0.clone()),
+        RLiteral::String(string_literal) => Ok(string_literal.0.clone().into()),
+        RLiteral::Decimal(decimal_literal) => {
+            let mut value = Vec::<u8>::new();
+            for digit in decimal_literal.0.bytes() {
+                let mut carry = (digit - b'0') as u32;
+                for byte in value.iter_mut().rev() {
+                    let x = *byte as u32 * 10 + carry;
+                    *byte = x as u8;
+                    carry = x >> 8;
+                }
+                if carry > 0 {
+                    value.insert(0, carry as u8);
+                }
+            }
+            if value.len() > 32 {
+                return Err(
+                    Diagnostic::new(Code::InvalidLiteral, "Decimal literal exceeds 32 bytes.").at_location(location),
+                );
+            }
+            if value.is_empty() {
+                value.push(0);
+            }
+            Ok(value.into())
+        },
+        RLiteral::Address(address_literal) => {
+            let address = &address_literal.0;
+            let lowercase = address.to_ascii_lowercase();
+            if address != &lowercase && address != &address.to_ascii_uppercase() {
+                let hash = keccak256(lowercase.as_bytes());
+                let checksummed = lowercase
+                    .chars()
+                    .enumerate()
+                    .map(|(index, c)| {
+                        let nibble = if index % 2 == 0 {
+                            hash[index / 2] >> 4
+                        } else {
+                            hash[index / 2] & 0x0f
+                        };
+                        if nibble >= 8 {
+                            c.to_ascii_uppercase()
+                        } else {
+                            c
+                        }
+                    })
+                    .collect::<String>();
+                if address != &checksummed {
+                    return Err(Diagnostic::new(
+                        Code::InvalidLiteral,
+                        format!("Invalid checksum of the address `0x{}`.", address),
+                    )
+                    .at_location(location)
+                    .with_note(format!("the checksummed address is `0x{}`", checksummed)));
+                }
+            }
+            Ok(hex::decode(address).unwrap().into())
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compile::file::compile_source;
+    use crate::compile::settings::CompilerSettings;
+    use crate::diagnostics::codes::Code;
+
+    #[test]
+    pub fn test_last_with_data_blocks() {
+        let input = "\
+contract A {
+    block main { caller push(d.pc) pop }
+    #[last]
+    block b { stop }
+    data block d { 0x01 }
+}";
+        let report = compile_source("a.mep", input, "A", CompilerSettings::default()).unwrap_err();
+        let codes = report
+            .diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.code)
+            .collect::<Vec<_>>();
+        assert_eq!(codes, [Code::InvalidAttribute]);
+
+        let input = input.replace("push(d.pc) pop ", "");
+        let (artifacts, _) = compile_source("a.mep", &input, "A", CompilerSettings::default()).unwrap();
+        assert_eq!(hex::encode(&artifacts.contracts["A"].bytecode), "3300");
+    }
+}
@@ -12,6 +12,19 @@ pub fn remap_contracts(mut contracts: HashMap<usize, Contract>, remapping: &Vec<
                     BlockItemInner::Contract(ref mut contract_index) => {
                         *contract_index = *remapping_map.get(contract_index).unwrap();
                     },
+                    BlockItemInner::Push(Push {
+                        attributes: _,
+                        inner:
+                            PushInner::ContractSize(contract_index)
+                            | PushInner::ContractCodehash(contract_index)
+                            | PushInner::Create2 {
+                                deployer: _,
+                                salt: _,
+                                index: contract_index,
+                            },
+                    }) => {
+                        *contract_index = *remapping_map.get(contract_index).unwrap();
+                    },
                     _ => (),
                 }
             }
@@ -0,0 +1,285 @@
+use std::collections::HashMap;
+
+use super::block_flow::{BlockFlow, BlockFlowBlockRef, BlockFlowItem};
+use super::opcode::{op_to_str, push_length, stack_io, OpCode, INVALID, JUMP, RETURN};
+use crate::ast::{RBlock, WithAttributes};
+use crate::diagnostics::codes::Code;
+use crate::diagnostics::diagnostic::{Diagnostic, Diagnostics};
+use crate::parser::parser::{Located, Location};
+
+const STOP: OpCode = 0x00;
+const REVERT: OpCode = 0xfd;
+const SELFDESTRUCT: OpCode = 0xff;
+
+/// Number of items a block takes from the stack and leaves on it, declared with `block name(in: 2, out: 1)` or
+/// `#[stack(a, b -> c)]`.
+#[derive(Clone, Debug)]
+pub struct StackSignature {
+    pub inputs: usize,
+    pub outputs: usize,
+    pub location: Location,
+}
+
+/// Effect of some code on the stack. `taken` is the number of items it needs on the stack when it starts.
+#[derive(Clone, Copy, Debug)]
+enum Effect {
+    /// Continues after its last item, with `delta` more items on the stack than when it started.
+    Continues { taken: usize, delta: isize },
+    /// Jumps or halts.
+    Ends { taken: usize },
+    /// Holds bytes which are not opcodes, or the code of a contract.
+    Unknown,
+}
+
+/// Symbolic stack depth along the items of a block, relative to its start.
+struct Walk<'a> {
+    /// Items on the stack when the block starts, `None` when inferring the effect of a block without signature.
+    inputs: Option<usize>,
+    signature: Option<&'a StackSignature>,
+    depth: isize,
+    taken: usize,
+}
+
+impl Walk<'_> {
+    /// Takes `count` items from the stack, failing when the block does not hold them.
+    fn take(&mut self, count: usize, error: impl FnOnce(usize) -> Diagnostic) -> Result<(), Diagnostic> {
+        let missing = count as isize - self.depth;
+        if missing > self.taken as isize {
+            self.taken = missing as usize;
+        }
+        match self.inputs {
+            Some(inputs) if self.taken > inputs => {
+                let available = (inputs as isize + self.depth).max(0) as usize;
+                let mut diagnostic = error(available);
+                if let Some(signature) = self.signature {
+                    diagnostic = diagnostic.with_label(
+                        &signature.location,
+                        format!("the block starts with {} items", signature.inputs),
+                    );
+                }
+                Err(diagnostic)
+            },
+            _ => Ok(()),
+        }
+    }
+}
+
+struct StackChecker<'a> {
+    r_blocks: &'a [Located<WithAttributes<Located<RBlock>>>],
+    blocks_flow: &'a HashMap<usize, BlockFlow>,
+    signatures: &'a [Option<StackSignature>],
+    /// Effects of the blocks without signature, inferred from their items.
+    inferred: HashMap<usize, Effect>,
+}
+
+/// Checks the items of the blocks with a stack signature: they must not take more items than the stack holds, and
+/// must leave the items declared by the signature. The blocks they refer to take and leave the items of their own
+/// signature, or the ones their items do. The blocks without signature are not checked, as they may be reached by
+/// jumps with any stack.
+///
+/// The blocks must not refer to each other recursively.
+pub fn check_stack_signatures(
+    r_blocks: &[Located<WithAttributes<Located<RBlock>>>],
+    blocks_flow: &HashMap<usize, BlockFlow>,
+    signatures: &[Option<StackSignature>],
+) -> Diagnostics {
+    let mut checker = StackChecker {
+        r_blocks,
+        blocks_flow,
+        signatures,
+        inferred: HashMap::new(),
+    };
+
+    let mut indexes = blocks_flow.keys().copied().collect::<Vec<_>>();
+    indexes.sort();
+
+    let mut errors = Diagnostics::default();
+    for index in indexes {
+        if signatures[index].is_some() {
+            if let Err(err) = checker.check(index) {
+                errors.push(err);
+            }
+        }
+    }
+    errors
+}
+
+impl StackChecker<'_> {
+    fn check(&mut self, index: usize) -> Result<(), Diagnostic> {
+        let signature = self.signatures[index].as_ref();
+        let (effect, last_location) = self.walk(index, signature.map(|signature| signature.inputs))?;
+
+        if let (Some(signature), Effect::Continues { taken: _, delta }) = (signature, effect) {
+            let left = signature.inputs as isize + delta;
+            if left != signature.outputs as isize {
+                let r_block = self.r_blocks[index].inner();
+                let mut diagnostic = Diagnostic::new(
+                    Code::StackMismatch,
+                    format!(
+                        "The block `{}` leaves {} items on the stack, but its signature declares {}.",
+                        r_block.name_str(),
+                        left,
+                        signature.outputs
+                    ),
+                )
+                .at_location(&signature.location);
+                if let Some(last_location) = last_location {
+                    diagnostic = diagnostic.with_label(&last_location, "the block ends here");
+                }
+                return Err(diagnostic);
+            }
+        }
+        Ok(())
+    }
+
+    /// Effect of a block on the stack, taken from its signature if it has one.
+    fn effect(&mut self, index: usize) -> Effect {
+        if let Some(signature) = &self.signatures[index] {
+            return Effect::Continues {
+                taken: signature.inputs,
+                delta: signature.outputs as isize - signature.inputs as isize,
+            };
+        }
+        if self.r_blocks[index].inner().data {
+            return Effect::Unknown;
+        }
+        if let Some(effect) = self.inferred.get(&index) {
+            return *effect;
+        }
+        let effect = self.walk(index, None).map_or(Effect::Unknown, |(effect, _)| effect);
+        self.inferred.insert(index, effect);
+        effect
+    }
+
+    /// Effect of the items of a block, and the location of the last one. With `inputs`, fails at the first item
+    /// taking more items than the stack holds.
+    fn walk(&mut self, index: usize, inputs: Option<usize>) -> Result<(Effect, Option<Location>), Diagnostic> {
+        let blocks_flow = self.blocks_flow;
+        let Some(block_flow) = blocks_flow.get(&index) else {
+            return Ok((Effect::Unknown, None));
+        };
+
+        let mut walk = Walk {
+            inputs,
+            signature: self.signatures[index].as_ref(),
+            depth: 0,
+            taken: 0,
+        };
+        let mut last_location = None::<Location>;
+
+        for (item, sources) in block_flow.items.iter().zip(&block_flow.sources) {
+            match item {
+                BlockFlowItem::Bytes(bytes) | BlockFlowItem::Data(bytes) => {
+                    let mut pc = 0;
+                    while pc < bytes.len() {
+                        let op = bytes[pc];
+                        let Some((pops, pushes)) = stack_io(op) else {
+                            return Ok((Effect::Unknown, last_location));
+                        };
+                        let location = sources
+                            .iter()
+                            .rev()
+                            .find(|source| source.offset <= pc)
+                            .map(|source| source.location.clone());
+                        walk.take(pops, |available| {
+                            let diagnostic = Diagnostic::new(
+                                Code::StackMismatch,
+                                format!(
+                                    "`{}` takes {} items from the stack, but it only holds {}.",
+                                    op_to_str(op).unwrap_or_default(),
+                                    pops,
+                                    available
+                                ),
+                            );
+                            match &location {
+                                Some(location) => diagnostic.at_location(location),
+                                None => diagnostic,
+                            }
+                        })?;
+                        walk.depth += pushes as isize - pops as isize;
+                        if location.is_some() {
+                            last_location = location;
+                        }
+                        if matches!(op, STOP | JUMP | RETURN | REVERT | INVALID | SELFDESTRUCT) {
+                            return Ok((Effect::Ends { taken: walk.taken }, last_location));
+                        }
+                        pc += 1 + push_length(op).unwrap_or_default();
+                    }
+                },
+                BlockFlowItem::Contract(_) => return Ok((Effect::Unknown, last_location)),
+                BlockFlowItem::Push(_) => {
+                    walk.depth += 1;
+                    if let Some(source) = sources.last() {
+                        last_location = Some(source.location.clone());
+                    }
+                },
+                BlockFlowItem::BlockEsp(block_ref) | BlockFlowItem::BlockStar(block_ref) => {
+                    let effect = self.effect(block_ref.index);
+                    let (Effect::Continues { taken, delta: _ } | Effect::Ends { taken }) = effect else {
+                        return Ok((Effect::Unknown, last_location));
+                    };
+                    walk.take(taken, |available| self.call_error(block_ref, taken, available))?;
+                    last_location = Some(block_ref.location.clone());
+                    match effect {
+                        Effect::Continues { taken: _, delta } => walk.depth += delta,
+                        _ => return Ok((Effect::Ends { taken: walk.taken }, last_location)),
+                    }
+                },
+            }
+        }
+
+        Ok((
+            Effect::Continues {
+                taken: walk.taken,
+                delta: walk.depth,
+            },
+            last_location,
+        ))
+    }
+
+    fn call_error(&self, block_ref: &BlockFlowBlockRef, taken: usize, available: usize) -> Diagnostic {
+        let diagnostic = Diagnostic::new(
+            Code::StackMismatch,
+            format!(
+                "`{}` takes {} items from the stack, but it only holds {}.",
+                self.r_blocks[block_ref.index].inner().name_str(),
+                taken,
+                available
+            ),
+        )
+        .at_location(&block_ref.location);
+        match &self.signatures[block_ref.index] {
+            Some(signature) => diagnostic.with_label(&signature.location, "signature declared here"),
+            None => diagnostic,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compile::file::compile_source;
+    use crate::compile::settings::CompilerSettings;
+    use crate::diagnostics::codes::Code;
+
+    fn codes(input: &str) -> Vec<Code> {
+        match compile_source("a.mep", input, "A", CompilerSettings::default()) {
+            Ok((_, report)) | Err(report) => report.diagnostics.iter().map(|diagnostic| diagnostic.code).collect(),
+        }
+    }
+
+    #[test]
+    pub fn test_stack_signatures() {
+        assert_eq!(
+            codes("contract A { block main { jumpdest pop push(main.pc) jump } }"),
+            []
+        );
+        assert_eq!(
+            codes("contract A { block main(in: 0, out: 0) { jumpdest pop push(main.pc) jump } }"),
+            [Code::StackMismatch]
+        );
+        assert_eq!(
+            codes("contract A { block main { &b stop } block b(in: 1025, out: 0) { pop } }"),
+            [Code::SyntaxError]
+        );
+    }
+}
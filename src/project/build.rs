@@ -0,0 +1,240 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use indexmap::IndexMap;
+
+use super::manifest::{Manifest, Target};
+use crate::compile::artifacts::Artifacts;
+use crate::compile::file::{compile_source_contracts, declared_contracts, enabled_contracts, read_source};
+use crate::compile::settings::CompilerSettings;
+use crate::diagnostics::report::Report;
+
+/// Contract whose artifacts were written by a build.
+pub struct BuiltContract {
+    pub target: String,
+    pub contract: String,
+    pub path: PathBuf,
+    pub artifacts: Artifacts,
+}
+
+/// Outcome of a build. Nothing is written when one of the reports holds errors.
+#[derive(Default)]
+pub struct BuildOutput {
+    pub reports: Vec<Report>,
+    pub built: Vec<BuiltContract>,
+}
+
+impl BuildOutput {
+    pub fn has_errors(&self) -> bool {
+        self.reports.iter().any(Report::has_errors)
+    }
+}
+
+/// Source file of a project, with the names of the contracts it declares.
+struct Source {
+    /// Path relative to the manifest.
+    relative_path: PathBuf,
+    path: String,
+    input: String,
+    contracts: Vec<String>,
+}
+
+/// Builds the target `target_name`, or all the targets, of the project whose manifest is at `manifest_path`. When
+/// the manifest declares no target, all the contracts are built with the default settings, as the target
+/// `default`. The errors of the manifest and of the file system are returned as messages.
+pub fn build_project(
+    manifest_path: &Path,
+    target_name: Option<&str>,
+    deny_warnings: bool,
+) -> Result<BuildOutput, String> {
+    let mut manifest = Manifest::load(manifest_path)?;
+    let root = manifest_path.parent().unwrap_or(Path::new(""));
+
+    if manifest.targets.is_empty() {
+        manifest.targets.insert("default".to_owned(), Target::default());
+    }
+    let targets = match target_name {
+        Some(target_name) => match manifest.targets.get_key_value(target_name) {
+            Some(target) => vec![target],
+            None => return Err(format!("No target `{}` in the manifest.", target_name)),
+        },
+        None => manifest.targets.iter().collect(),
+    };
+
+    let mut output = BuildOutput::default();
+    let mut sources = Vec::<Source>::new();
+    for relative_path in source_files(root, &manifest.project.sources)? {
+        let path = root.join(&relative_path).to_string_lossy().into_owned();
+        let input = match read_source(&path) {
+            Ok(input) => input,
+            Err(report) => {
+                output.reports.push(report);
+                continue;
+            },
+        };
+        match declared_contracts(&path, &input) {
+            Ok(contracts) => sources.push(Source {
+                relative_path,
+                path,
+                input,
+                contracts,
+            }),
+            Err(report) => output.reports.push(report),
+        }
+    }
+    if output.has_errors() {
+        return Ok(output);
+    }
+
+    let mut written = Vec::<(BuiltContract, String)>::new();
+    for (name, target) in targets {
+        let settings = target.compiler_settings();
+        let directory = match &target.output {
+            Some(output) => root.join(output),
+            None => root.join(&manifest.project.build_dir).join(name),
+        };
+
+        for (source_index, contracts) in target_contracts(name, target, &settings, &sources)? {
+            let source = &sources[source_index];
+            let (compiled, mut report) = compile_source_contracts(&source.path, &source.input, &contracts, &settings);
+            if deny_warnings {
+                report.deny_warnings();
+            }
+            output.reports.push(report);
+
+            for artifacts in compiled {
+                let content = target.format.content(&artifacts, settings.push0);
+                let built = BuiltContract {
+                    target: name.clone(),
+                    contract: artifacts.main.clone(),
+                    path: target.format.path(&directory, &source.path, &artifacts.main),
+                    artifacts,
+                };
+                written.push((built, content));
+            }
+        }
+    }
+    if output.has_errors() {
+        return Ok(output);
+    }
+
+    for (built, content) in written {
+        if let Some(directory) = built.path.parent() {
+            std::fs::create_dir_all(directory)
+                .map_err(|err| format!("Could not create the directory `{}`: {}", directory.display(), err))?;
+        }
+        std::fs::write(&built.path, content)
+            .map_err(|err| format!("Could not write the file `{}`: {}", built.path.display(), err))?;
+        output.built.push(built);
+    }
+
+    Ok(output)
+}
+
+/// Files a build of the project depends on: the manifest and the sources. The files which cannot be listed are
+/// omitted.
+pub fn project_files(manifest_path: &Path) -> Vec<PathBuf> {
+    let mut res = vec![manifest_path.to_owned()];
+    if let Ok(manifest) = Manifest::load(manifest_path) {
+        let root = manifest_path.parent().unwrap_or(Path::new(""));
+        for relative_path in source_files(root, &manifest.project.sources).unwrap_or_default() {
+            res.push(root.join(relative_path));
+        }
+    }
+    res
+}
+
+/// Contracts built by a target, grouped by source file, in the order of the target or of the sources.
+fn target_contracts(
+    target_name: &str,
+    target: &Target,
+    settings: &CompilerSettings,
+    sources: &[Source],
+) -> Result<IndexMap<usize, Vec<String>>, String> {
+    let mut res = IndexMap::<usize, Vec<String>>::new();
+    let mut names = HashSet::<String>::new();
+    let mut add = |source_index: usize, name: &str| {
+        if !names.insert(name.to_owned()) {
+            return Err(format!(
+                "The target `{}` builds many contracts named `{}`, whose artifacts would overwrite each other.",
+                target_name, name
+            ));
+        }
+        res.entry(source_index).or_default().push(name.to_owned());
+        Ok(())
+    };
+
+    if target.contracts.is_empty() {
+        for (source_index, source) in sources.iter().enumerate() {
+            // the sources are already parsed
+            for name in enabled_contracts(&source.path, &source.input, settings).unwrap_or_default() {
+                add(source_index, &name)?;
+            }
+        }
+        return Ok(res);
+    }
+
+    for entry in &target.contracts {
+        let (file, name) = match entry.rsplit_once(':') {
+            Some((file, name)) => (Some(Path::new(file)), name),
+            None => (None, entry.as_str()),
+        };
+        let candidates = sources
+            .iter()
+            .enumerate()
+            .filter(|(_, source)| file.is_none() || file == Some(source.relative_path.as_path()))
+            .filter(|(_, source)| source.contracts.iter().any(|contract| contract == name))
+            .collect::<Vec<_>>();
+        match candidates.as_slice() {
+            [] => {
+                return Err(format!(
+                    "Contract `{}` of the target `{}` not found in the sources.",
+                    entry, target_name
+                ))
+            },
+            [(source_index, _)] => add(*source_index, name)?,
+            _ => {
+                return Err(format!(
+                    "Contract `{}` of the target `{}` is declared in many files, write it as `path/to/file.mep:{}`.",
+                    entry, target_name, name
+                ))
+            },
+        }
+    }
+    Ok(res)
+}
+
+/// `.mep` files of the sources, relative to `root`. The directories are searched recursively, in alphabetical
+/// order.
+fn source_files(root: &Path, sources: &[String]) -> Result<Vec<PathBuf>, String> {
+    fn visit(root: &Path, relative_path: &Path, res: &mut Vec<PathBuf>) -> Result<(), String> {
+        let path = root.join(relative_path);
+        if !path.is_dir() {
+            res.push(relative_path.to_owned());
+            return Ok(());
+        }
+
+        let entries = std::fs::read_dir(&path)
+            .map_err(|err| format!("Could not read the directory `{}`: {}", path.display(), err))?;
+        let mut children = entries
+            .filter_map(Result::ok)
+            .map(|entry| relative_path.join(entry.file_name()))
+            .collect::<Vec<_>>();
+        children.sort();
+        for child in children {
+            if root.join(&child).is_dir() || child.extension().is_some_and(|extension| extension == "mep") {
+                visit(root, &child, res)?;
+            }
+        }
+        Ok(())
+    }
+
+    let mut res = Vec::new();
+    for source in sources {
+        if !root.join(source).exists() {
+            return Err(format!("Source `{}` of the manifest not found.", source));
+        }
+        visit(root, Path::new(source), &mut res)?;
+    }
+    Ok(res)
+}
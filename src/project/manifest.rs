@@ -0,0 +1,105 @@
+use std::path::Path;
+
+use indexmap::IndexMap;
+use serde::Deserialize;
+
+use crate::compile::artifacts::ArtifactFormat;
+use crate::compile::settings::CompilerSettings;
+
+/// File name of the manifest of a project.
+pub const MANIFEST_FILE: &str = "meplang.toml";
+
+/// Manifest of a project (`meplang.toml`), listing its sources and the targets to build.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct Manifest {
+    #[serde(default)]
+    pub project: ProjectConfig,
+    /// Targets of the project, in the order of the manifest.
+    #[serde(default)]
+    pub targets: IndexMap<String, Target>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct ProjectConfig {
+    /// Files and directories holding the sources, relative to the manifest. The directories are searched
+    /// recursively for `.mep` files.
+    #[serde(default = "default_sources")]
+    pub sources: Vec<String>,
+    /// Directory of the artifacts, relative to the manifest.
+    #[serde(default = "default_build_dir")]
+    pub build_dir: String,
+}
+
+impl Default for ProjectConfig {
+    fn default() -> Self {
+        Self {
+            sources: default_sources(),
+            build_dir: default_build_dir(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct Target {
+    /// Contracts to build, as `Name` or `path/to/file.mep:Name` when the name is declared in many files. All the
+    /// contracts of the sources are built when empty.
+    #[serde(default)]
+    pub contracts: Vec<String>,
+    /// Hard fork the bytecode is deployed on. It takes precedence over the setting `push0`.
+    #[serde(default)]
+    pub evm_version: Option<EvmVersion>,
+    #[serde(default)]
+    pub settings: CompilerSettings,
+    /// Directory of the artifacts of the target, relative to the manifest. `<buildDir>/<target>` by default.
+    #[serde(default)]
+    pub output: Option<String>,
+    #[serde(default)]
+    pub format: ArtifactFormat,
+}
+
+impl Target {
+    /// Compiler settings of the target, with the EVM version applied.
+    pub fn compiler_settings(&self) -> CompilerSettings {
+        let mut settings = self.settings.clone();
+        if let Some(evm_version) = self.evm_version {
+            settings.push0 = evm_version.has_push0();
+        }
+        settings
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EvmVersion {
+    London,
+    Paris,
+    Shanghai,
+    Cancun,
+    Prague,
+}
+
+impl EvmVersion {
+    /// Whether the opcode `PUSH0` exists (EIP-3855).
+    pub fn has_push0(self) -> bool {
+        self >= Self::Shanghai
+    }
+}
+
+impl Manifest {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|err| format!("Could not open file `{}`: {}", path.display(), err))?;
+        toml::from_str(&content).map_err(|err| format!("Invalid manifest `{}`: {}", path.display(), err))
+    }
+}
+
+fn default_sources() -> Vec<String> {
+    vec!["src".to_owned()]
+}
+
+fn default_build_dir() -> String {
+    "build".to_owned()
+}
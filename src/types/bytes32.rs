@@ -1,4 +1,4 @@
-use std::ops::{Deref, DerefMut};
+use std::ops::{Add, Deref, DerefMut, Mul, Neg, Not, Sub};
 
 use bytes::Bytes;
 
@@ -25,6 +25,77 @@ impl From<[u8; 32]> for Bytes32 {
     }
 }
 
+/// Bitwise not, as the opcode `not`.
+impl Not for &Bytes32 {
+    type Output = Bytes32;
+
+    fn not(self) -> Bytes32 {
+        Bytes32(self.0.map(|byte| !byte))
+    }
+}
+
+/// Two's complement opposite, modulo 2^256.
+impl Neg for &Bytes32 {
+    type Output = Bytes32;
+
+    fn neg(self) -> Bytes32 {
+        let mut res = !self;
+        for byte in res.iter_mut().rev() {
+            let (sum, overflow) = byte.overflowing_add(1);
+            *byte = sum;
+            if !overflow {
+                break;
+            }
+        }
+        res
+    }
+}
+
+/// Sum modulo 2^256, as the opcode `add`.
+impl Add for &Bytes32 {
+    type Output = Bytes32;
+
+    fn add(self, rhs: &Bytes32) -> Bytes32 {
+        let mut res = Bytes32::default();
+        let mut carry = 0u16;
+        for i in (0..32).rev() {
+            let sum = self[i] as u16 + rhs[i] as u16 + carry;
+            res[i] = sum as u8;
+            carry = sum >> 8;
+        }
+        res
+    }
+}
+
+/// Difference modulo 2^256, as the opcode `sub`.
+impl Sub for &Bytes32 {
+    type Output = Bytes32;
+
+    fn sub(self, rhs: &Bytes32) -> Bytes32 {
+        self + &-rhs
+    }
+}
+
+/// Product modulo 2^256, as the opcode `mul`.
+impl Mul for &Bytes32 {
+    type Output = Bytes32;
+
+    fn mul(self, rhs: &Bytes32) -> Bytes32 {
+        let mut res = Bytes32::default();
+        for i in (0..32).rev() {
+            let mut carry = 0u32;
+            // the bytes of the product above the 32nd are dropped
+            for j in (31 - i..32).rev() {
+                let k = i + j - 31;
+                let x = res[k] as u32 + self[i] as u32 * rhs[j] as u32 + carry;
+                res[k] = x as u8;
+                carry = x >> 8;
+            }
+        }
+        res
+    }
+}
+
 impl Bytes32 {
     pub fn from_bytes(bytes: &Bytes, right_padding: bool) -> Option<Self> {
         if bytes.len() > 32 {
@@ -39,6 +110,12 @@ impl Bytes32 {
         Some(Self(res))
     }
 
+    pub fn from_usize(value: usize) -> Self {
+        let mut res = [0; 32];
+        res[24..].copy_from_slice(&(value as u64).to_be_bytes());
+        Self(res)
+    }
+
     pub fn leading_zeros(&self) -> usize {
         let mut i = 0;
         while self[i] == 0 {
@@ -58,3 +135,29 @@ impl Bytes32 {
         &self[self.leading_zeros()..32]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_hex(value: &str) -> Bytes32 {
+        Bytes32::from_bytes(&hex::decode(value).unwrap().into(), true).unwrap()
+    }
+
+    #[test]
+    pub fn test_arithmetic() {
+        let max = Bytes32([0xff; 32]);
+        assert_eq!(&(&max * &from_hex("02")) + &from_hex("03"), from_hex("01"));
+        assert_eq!(&max + &from_hex("01"), Bytes32::default());
+
+        // carries across byte boundaries
+        assert_eq!(&from_hex("ffff") * &from_hex("ffff"), from_hex("fffe0001"));
+        assert_eq!(&from_hex("0100") * &from_hex("0100"), from_hex("010000"));
+        assert_eq!(&from_hex("ff") + &from_hex("01"), from_hex("0100"));
+        assert_eq!(&from_hex("80") * &max, -&from_hex("80"));
+
+        assert_eq!(&from_hex("02") - &from_hex("05"), -&from_hex("03"));
+        assert_eq!(&from_hex("00") - &from_hex("01"), max);
+        assert_eq!(&from_hex("0100") - &from_hex("01"), from_hex("ff"));
+    }
+}
@@ -0,0 +1,14 @@
+use tiny_keccak::{Hasher, Keccak};
+
+pub fn keccak256(bytes: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak::v256();
+    hasher.update(bytes);
+    let mut res = [0u8; 32];
+    hasher.finalize(&mut res);
+    res
+}
+
+pub fn selector(signature: &str) -> [u8; 4] {
+    let hash = keccak256(signature.as_bytes());
+    [hash[0], hash[1], hash[2], hash[3]]
+}
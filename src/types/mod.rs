@@ -1 +1,2 @@
 pub mod bytes32;
+pub mod hash;